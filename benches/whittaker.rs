@@ -1,5 +1,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use whittaker_eilers::{CrossValidationResult, OptimisedSmoothResult, WhittakerSmoother};
+use whittaker_eilers::{
+    BlockLayout, CrossValidationResult, OptimisedSmoothResult, WhittakerSmoother,
+};
 
 fn new_y_whittaker(y: &Vec<f64>) -> Vec<f64> {
     WhittakerSmoother::new(2e4, 2, y.len(), None, None)
@@ -18,6 +20,20 @@ fn new_parallel_y_whittaker(y: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
         .collect()
 }
 
+fn new_block_y_whittaker(y_block: &[f64], series: usize, output: &mut [f64]) {
+    let length = y_block.len() / series;
+    let smoother = WhittakerSmoother::new(2e4, 2, length, None, None).unwrap();
+
+    smoother
+        .smooth_block(
+            y_block,
+            series,
+            BlockLayout::ColumnMajor { stride: length },
+            output,
+        )
+        .unwrap()
+}
+
 fn new_y_whittaker_cross_validate(y: &Vec<f64>) -> CrossValidationResult {
     WhittakerSmoother::new(2e4, 2, y.len(), None, None)
         .unwrap()
@@ -58,6 +74,12 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     // let repeat_wood_data = [wood_data_vec.as_slice(); 100000];
 
+    let block_series = 100000;
+    let block_wood_data: Vec<f64> = (0..block_series)
+        .flat_map(|_| wood_data_vec.iter().copied())
+        .collect();
+    let mut block_output = vec![0.0; block_wood_data.len()];
+
     let reusable_smoother = WhittakerSmoother::new(
         2e4,
         2,
@@ -94,6 +116,12 @@ fn criterion_benchmark(c: &mut Criterion) {
         b.iter(|| reusable_smoother.smooth(&wood_data_vec).unwrap())
     });
 
+    c.bench_function("Whittaker Wood Y Only Block", |b| {
+        b.iter(|| {
+            new_block_y_whittaker(black_box(&block_wood_data), block_series, &mut block_output)
+        })
+    });
+
     #[cfg(feature = "rayon")]
     c.bench_function("Whittaker Wood Y Only Parallel", |b| {
         b.iter(|| new_parallel_y_whittaker(black_box(&repeat_wood_data)))
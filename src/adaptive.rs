@@ -0,0 +1,111 @@
+use crate::float::{cast, WhittakerFloat};
+use crate::robust::median;
+
+/// The smallest local roughness, relative to the median, used when estimating penalty weights. Limits the largest penalty weight
+/// to 100 times the typical one, so perfectly flat stretches don't make the system ill-conditioned.
+const ROUGHNESS_FLOOR: f64 = 1e-4;
+
+/// The result of smoothing with spatially adaptive penalty weights.
+#[derive(Clone, Debug)]
+pub struct AdaptiveSmoothResult<T = f64> {
+    /// The smoothed data.
+    pub smoothed: Vec<T>,
+    /// The penalty weight of each point used for the final smooth. These include any penalty weights the smoother already had, so
+    /// they can be passed straight to `update_penalty_weights`.
+    pub penalty_weights: Vec<T>,
+}
+
+/// Spreads per-point penalty weights onto the differences of the given order.
+///
+/// Each difference spans `order + 1` neighbouring points and takes the mean of their penalty weights. Periodic differences wrap
+/// around the end of the data.
+pub(crate) fn difference_weights<T: WhittakerFloat>(
+    point_weights: &[T],
+    order: usize,
+    periodic: bool,
+) -> Vec<T> {
+    let data_length = point_weights.len();
+    let differences = if periodic {
+        data_length
+    } else {
+        data_length - order
+    };
+
+    (0..differences)
+        .map(|i| mean(point_weights, &span(i, 0, order, data_length, periodic)))
+        .collect()
+}
+
+/// Estimates per-point penalty weights from the differences of a first-pass fit.
+///
+/// The squared differences touching each point are averaged, then averaged again over `window` points either side, to give the local
+/// roughness. Each penalty weight is the square root of the median roughness over the local roughness, so rough stretches such as
+/// sharp peaks are penalised less and flat stretches more. The weights are normalised to a geometric mean of 1 so that lambda keeps
+/// its meaning.
+pub(crate) fn roughness_penalty_weights<T: WhittakerFloat>(
+    differences: &[T],
+    order: usize,
+    window: usize,
+    periodic: bool,
+) -> Vec<T> {
+    let data_length = if periodic {
+        differences.len()
+    } else {
+        differences.len() + order
+    };
+
+    let squared_differences = differences.iter().map(|d| *d * *d).collect::<Vec<T>>();
+    let point_roughness = (0..data_length)
+        .map(|j| {
+            let touching = if periodic {
+                span(j, order, 0, data_length, true)
+            } else {
+                span(j, order, 0, differences.len(), false)
+            };
+            mean(&squared_differences, &touching)
+        })
+        .collect::<Vec<T>>();
+
+    let local_roughness = (0..data_length)
+        .map(|j| {
+            mean(
+                &point_roughness,
+                &span(j, window, window, data_length, periodic),
+            )
+        })
+        .collect::<Vec<T>>();
+
+    let scale = median(&mut local_roughness.clone());
+
+    if scale <= T::min_positive_value() {
+        return vec![T::one(); data_length];
+    }
+
+    let floor = scale * cast(ROUGHNESS_FLOOR);
+    let weights = local_roughness
+        .iter()
+        .map(|r| (scale / r.max(floor)).sqrt())
+        .collect::<Vec<T>>();
+
+    let geometric_mean =
+        (weights.iter().map(|v| v.ln()).sum::<T>() / cast(data_length as f64)).exp();
+
+    weights.iter().map(|v| *v / geometric_mean).collect()
+}
+
+/// The indices from `before` below `centre` to `after` above it. Open spans are clipped to `0..length`, periodic spans wrap around it.
+fn span(centre: usize, before: usize, after: usize, length: usize, periodic: bool) -> Vec<usize> {
+    if periodic {
+        let first = (centre as isize - before as isize).rem_euclid(length as isize) as usize;
+        (0..(before + after + 1).min(length))
+            .map(|k| (first + k) % length)
+            .collect()
+    } else {
+        (centre.saturating_sub(before)..(centre + after + 1).min(length)).collect()
+    }
+}
+
+/// The mean of the values at the given indices.
+fn mean<T: WhittakerFloat>(values: &[T], indices: &[usize]) -> T {
+    indices.iter().map(|i| values[*i]).sum::<T>() / cast(indices.len() as f64)
+}
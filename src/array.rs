@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+
+use ndarray::{Array1, Array2, ArrayView, ArrayView1, ArrayView2, Axis, Dimension};
+#[cfg(feature = "rayon")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+#[cfg(feature = "rayon")]
+use rayon::slice::{ParallelSlice, ParallelSliceMut};
+
+#[cfg(feature = "rayon")]
+use crate::block::BLOCK_TILE_SIZE;
+use crate::errors::WhittakerError;
+use crate::float::WhittakerFloat;
+use crate::{BlockLayout, WhittakerSmoother};
+
+impl<T: WhittakerFloat> WhittakerSmoother<T> {
+    /// Create a new Whittaker-Eilers smoother and interpolator from [ndarray] arrays.
+    ///
+    /// The same as `new`, but taking the x input and weights as array views, which may be strided.
+    ///
+    /// # Arguments:
+    /// * `lambda`: Controls the smoothing strength, the larger, the smoother.
+    /// * `order`: The order of the filter.
+    /// * `data_length`: The length of the data which is to be smoothed.
+    /// * `x_input`: The time/position at which the y measurement was taken. Used to smooth unequally spaced data. Must be monotonically increasing.
+    /// * `weights`: The weight of each y measurement.
+    pub fn new_from_arrays(
+        lambda: T,
+        order: usize,
+        data_length: usize,
+        x_input: Option<ArrayView1<T>>,
+        weights: Option<ArrayView1<T>>,
+    ) -> Result<WhittakerSmoother<T>, WhittakerError> {
+        let x_input = x_input.map(|x_input| x_input.to_vec());
+        let weights = weights.map(|weights| weights.to_vec());
+
+        Self::new(
+            lambda,
+            order,
+            data_length,
+            x_input.as_ref(),
+            weights.as_ref(),
+        )
+    }
+
+    /// Updates the weights of the data to be smoothed from an [ndarray] array. See `update_weights`.
+    ///
+    /// # Arguments:
+    /// * `weights`: The weights of the measurements to be smoothed.
+    pub fn update_weights_array(&mut self, weights: ArrayView1<T>) -> Result<(), WhittakerError> {
+        self.update_weights(&weights.to_vec())
+    }
+
+    /// Run Whittaker-Eilers smoothing and interpolation on an [ndarray] array.
+    ///
+    /// The data is only copied if it isn't contiguous.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed and interpolated by the Whittaker-Eilers smoother.
+    ///
+    /// # Returns:
+    /// The smoothed and interpolated data.
+    pub fn smooth_array(&self, y_input: ArrayView1<T>) -> Result<Array1<T>, WhittakerError> {
+        Ok(Array1::from(self.smooth(&standard_slice(&y_input))?))
+    }
+
+    /// Run Whittaker-Eilers smoothing and interpolation on every lane of a two-dimensional [ndarray] array along an axis.
+    ///
+    /// Each lane along `axis` is a series as long as the smoother's data, so smoothing along `Axis(0)` smooths every column and
+    /// along `Axis(1)` every row. The series are solved together with `smooth_block`, a tile at a time, and with the `rayon` feature
+    /// the tiles are smoothed in parallel. The data is only copied if the lanes aren't already contiguous.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed and interpolated, with a series in each lane along `axis`.
+    /// * `axis`: The axis to smooth along.
+    ///
+    /// # Returns:
+    /// The smoothed and interpolated data, with the same shape as `y_input`. Errors if `axis` isn't `Axis(0)` or `Axis(1)`.
+    pub fn smooth_axis(
+        &self,
+        y_input: ArrayView2<T>,
+        axis: Axis,
+    ) -> Result<Array2<T>, WhittakerError> {
+        if axis.index() > 1 {
+            return Err(WhittakerError::InvalidParameter("axis"));
+        }
+
+        let data_length = self.get_data_length();
+        if y_input.len_of(axis) != data_length {
+            return Err(WhittakerError::LengthMismatch(
+                data_length,
+                y_input.len_of(axis),
+            ));
+        }
+
+        // Put the series in rows, so that in standard layout each one is contiguous.
+        let series_rows = if axis == Axis(0) {
+            y_input.reversed_axes()
+        } else {
+            y_input
+        };
+        let series = series_rows.nrows();
+        let y_block = standard_slice(&series_rows);
+        let mut smoothed = vec![T::zero(); y_block.len()];
+
+        if data_length > 0 {
+            let layout = BlockLayout::ColumnMajor {
+                stride: data_length,
+            };
+
+            #[cfg(feature = "rayon")]
+            {
+                let tile_length = BLOCK_TILE_SIZE.max(data_length) / data_length * data_length;
+                y_block
+                    .par_chunks(tile_length)
+                    .zip(smoothed.par_chunks_mut(tile_length))
+                    .try_for_each(|(y_tile, smoothed_tile)| {
+                        self.smooth_block(y_tile, y_tile.len() / data_length, layout, smoothed_tile)
+                    })?;
+            }
+            #[cfg(not(feature = "rayon"))]
+            self.smooth_block(&y_block, series, layout, &mut smoothed)?;
+        }
+
+        let smoothed = Array2::from_shape_vec((series, data_length), smoothed)
+            .map_err(|_| WhittakerError::LengthMismatch(series * data_length, y_block.len()))?;
+
+        if axis == Axis(0) {
+            Ok(smoothed.reversed_axes())
+        } else {
+            Ok(smoothed)
+        }
+    }
+}
+
+/// Borrows the elements of an array in standard (row-major) order, only copying them if they aren't already contiguous in that
+/// order.
+fn standard_slice<'a, T: Clone, D: Dimension>(array: &'a ArrayView<T, D>) -> Cow<'a, [T]> {
+    match array.as_slice() {
+        Some(slice) => Cow::Borrowed(slice),
+        None => Cow::Owned(array.iter().cloned().collect()),
+    }
+}
@@ -0,0 +1,153 @@
+use crate::block::{divide_rows, subtract_row};
+use crate::float::WhittakerFloat;
+use sprs::CsMat;
+
+/// LDLᵀ factorisation of a symmetric banded matrix which stores only the bands.
+///
+/// The system `W + λD'D` solved by the smoother has a bandwidth equal to the order of the penalty, so its factor `L` has the same
+/// bandwidth with no fill-in. Factorising takes `O(n·d²)` time and `O(n·d)` memory without any reordering or symmetry check. Column
+/// `j` of `L` below the diagonal is stored at `l[j * bandwidth..(j + 1) * bandwidth]`, so `L_ij` lives at `l[j * bandwidth + i - j - 1]`.
+#[derive(Clone)]
+pub(crate) struct BandedLdl<T> {
+    bandwidth: usize,
+    l: Vec<T>,
+    d: Vec<T>,
+}
+
+impl<T: WhittakerFloat> BandedLdl<T> {
+    /// Factorises a symmetric matrix with the given bandwidth. Only its lower triangle is read.
+    ///
+    /// Returns `None` if a pivot is not positive, in which case the matrix is either singular or not positive definite.
+    pub(crate) fn new(matrix: &CsMat<T>, bandwidth: usize) -> Option<BandedLdl<T>> {
+        let n = matrix.rows();
+        let mut l = vec![T::zero(); n * bandwidth];
+        let mut d = vec![T::zero(); n];
+
+        // Copy the lower bands into the storage of L, and the diagonal into d, before factorising in place.
+        for (value, (row, column)) in matrix.iter() {
+            if row == column {
+                d[row] += *value;
+            } else if row > column && row - column <= bandwidth {
+                l[column * bandwidth + row - column - 1] += *value;
+            }
+        }
+
+        for j in 0..n {
+            let pivot = d[j]
+                - (j.saturating_sub(bandwidth)..j)
+                    .map(|k| l[k * bandwidth + j - k - 1].powi(2) * d[k])
+                    .sum::<T>();
+            d[j] = pivot;
+            if pivot.is_nan() || pivot <= T::zero() {
+                return None;
+            }
+
+            for i in j + 1..n.min(j + bandwidth + 1) {
+                let mut value = l[j * bandwidth + i - j - 1];
+                for k in i.saturating_sub(bandwidth)..j {
+                    value -= l[k * bandwidth + i - k - 1] * l[k * bandwidth + j - k - 1] * d[k];
+                }
+                l[j * bandwidth + i - j - 1] = value / d[j];
+            }
+        }
+
+        Some(BandedLdl { bandwidth, l, d })
+    }
+
+    /// The diagonal of `D`.
+    pub(crate) fn d(&self) -> &[T] {
+        &self.d
+    }
+
+    /// Solves `A x = b` through forward and backward substitution.
+    pub(crate) fn solve(&self, rhs: &[T]) -> Vec<T> {
+        let n = self.d.len();
+        let bandwidth = self.bandwidth;
+        let mut x = rhs.to_vec();
+
+        for i in 0..n {
+            let correction = (i.saturating_sub(bandwidth)..i)
+                .map(|k| self.l[k * bandwidth + i - k - 1] * x[k])
+                .sum::<T>();
+            x[i] -= correction;
+        }
+        for (value, d) in x.iter_mut().zip(&self.d) {
+            *value /= *d;
+        }
+        for i in (0..n).rev() {
+            let correction = (i + 1..n.min(i + bandwidth + 1))
+                .map(|k| self.l[i * bandwidth + k - i - 1] * x[k])
+                .sum::<T>();
+            x[i] -= correction;
+        }
+        x
+    }
+
+    /// Solves `A X = B` in place for several right hand sides at once.
+    ///
+    /// The block is stored row by row, so row `i` of every right hand side is at `block[i * columns..(i + 1) * columns]` and each
+    /// element of `L` is applied to all of them in a single pass.
+    pub(crate) fn solve_block(&self, block: &mut [T], columns: usize) {
+        let n = self.d.len();
+        let bandwidth = self.bandwidth;
+
+        for i in 0..n {
+            for k in i.saturating_sub(bandwidth)..i {
+                subtract_row(block, columns, i, k, self.l[k * bandwidth + i - k - 1]);
+            }
+        }
+        divide_rows(block, columns, &self.d);
+        for i in (0..n).rev() {
+            for k in i + 1..n.min(i + bandwidth + 1) {
+                subtract_row(block, columns, i, k, self.l[i * bandwidth + k - i - 1]);
+            }
+        }
+    }
+
+    /// Computes the diagonal of `A^-1` using the Takahashi recurrences restricted to the band.
+    ///
+    /// See [crate::selected_inversion::inverse_diagonal]. Every element of the inverse needed by the recurrences lies within the band,
+    /// so they are stored in the same layout as `L`.
+    pub(crate) fn inverse_diagonal(&self) -> Vec<T> {
+        let n = self.d.len();
+        let bandwidth = self.bandwidth;
+
+        // The strictly lower band of Z, with Z_ij at z[j * bandwidth + i - j - 1].
+        let mut z = vec![T::zero(); n * bandwidth];
+        let mut z_diagonal = vec![T::zero(); n];
+
+        let z_lookup = |z: &[T], z_diagonal: &[T], row: usize, column: usize| -> T {
+            match row.cmp(&column) {
+                std::cmp::Ordering::Equal => z_diagonal[row],
+                std::cmp::Ordering::Greater => z[column * bandwidth + row - column - 1],
+                std::cmp::Ordering::Less => z[row * bandwidth + column - row - 1],
+            }
+        };
+
+        for j in (0..n).rev() {
+            let last = n.min(j + bandwidth + 1);
+
+            for i in (j + 1..last).rev() {
+                let value = -(j + 1..last)
+                    .map(|k| self.l[j * bandwidth + k - j - 1] * z_lookup(&z, &z_diagonal, i, k))
+                    .sum::<T>();
+                z[j * bandwidth + i - j - 1] = value;
+            }
+
+            z_diagonal[j] = T::one() / self.d[j]
+                - (j + 1..last)
+                    .map(|k| self.l[j * bandwidth + k - j - 1] * z[j * bandwidth + k - j - 1])
+                    .sum::<T>();
+        }
+        z_diagonal
+    }
+}
+
+/// The largest distance of any stored element from the diagonal.
+pub(crate) fn bandwidth<T>(matrix: &CsMat<T>) -> usize {
+    matrix
+        .iter()
+        .map(|(_, (row, column))| row.abs_diff(column))
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,129 @@
+use crate::float::{cast, WhittakerFloat};
+
+/// The asymmetric least squares variant used to estimate a baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BaselineMethod {
+    /// Asymmetric least squares from Eilers & Boelens (2005). Points above the baseline are given weight `p` and points below it `1 - p`.
+    /// Contains `p`, which must lie between 0 and 1 and is usually 0.001 - 0.1.
+    Als(f64),
+    /// Asymmetrically reweighted penalized least squares from Baek et al. (2015). Weights are set by a logistic function of the
+    /// residuals, scaled by the mean and standard deviation of the residuals below the baseline.
+    ArPls,
+    /// Adaptive iteratively reweighted penalized least squares from Zhang et al. (2010). Points above the baseline are given zero weight
+    /// and points below it an exponentially increasing weight.
+    AirPls,
+}
+
+/// The result of estimating a baseline.
+#[derive(Clone, Debug)]
+pub struct BaselineResult<T = f64> {
+    /// The estimated baseline.
+    pub baseline: Vec<T>,
+    /// The input data with the baseline subtracted.
+    pub corrected: Vec<T>,
+    /// The number of iterations that were run.
+    pub iterations: usize,
+    /// Whether the method met its convergence criterion before the maximum number of iterations was reached.
+    pub converged: bool,
+}
+
+/// Computes the next set of asymmetric weights. Returns `None` once the method has converged.
+pub(crate) fn asymmetric_weights<T: WhittakerFloat>(
+    method: BaselineMethod,
+    y_input: &[T],
+    baseline: &[T],
+    weights: &[T],
+    iteration: usize,
+    tolerance: T,
+) -> Option<Vec<T>> {
+    let residuals = y_input
+        .iter()
+        .zip(baseline)
+        .map(|(y, z)| *y - *z)
+        .collect::<Vec<T>>();
+
+    let negative_residuals = residuals
+        .iter()
+        .filter(|d| **d < T::zero())
+        .copied()
+        .collect::<Vec<T>>();
+
+    let two = cast::<T>(2.0);
+
+    let new_weights = match method {
+        BaselineMethod::Als(p) => {
+            let p = cast::<T>(p);
+            residuals
+                .iter()
+                .map(|d| if *d > T::zero() { p } else { T::one() - p })
+                .collect::<Vec<T>>()
+        }
+        BaselineMethod::ArPls => {
+            if negative_residuals.len() < 2 {
+                return None;
+            }
+            let count = cast::<T>(negative_residuals.len() as f64);
+            let mean = negative_residuals.iter().sum::<T>() / count;
+            let std = (negative_residuals
+                .iter()
+                .map(|d| (*d - mean).powi(2))
+                .sum::<T>()
+                / (count - T::one()))
+            .sqrt();
+
+            if std <= T::epsilon() {
+                return None;
+            }
+
+            residuals
+                .iter()
+                .map(|d| T::one() / (T::one() + (two * (*d - (two * std - mean)) / std).exp()))
+                .collect::<Vec<T>>()
+        }
+        BaselineMethod::AirPls => {
+            let negative_sum = negative_residuals.iter().sum::<T>().abs();
+            let total = y_input.iter().map(|y| y.abs()).sum::<T>();
+
+            if negative_residuals.is_empty() || negative_sum < tolerance * total {
+                return None;
+            }
+
+            let t = cast::<T>(iteration as f64);
+            let mut new_weights = residuals
+                .iter()
+                .map(|d| {
+                    if *d >= T::zero() {
+                        T::zero()
+                    } else {
+                        (t * d.abs() / negative_sum).exp()
+                    }
+                })
+                .collect::<Vec<T>>();
+
+            let largest_negative = negative_residuals
+                .iter()
+                .copied()
+                .fold(T::neg_infinity(), T::max);
+            let end_weight = (t * largest_negative / negative_sum).exp();
+            let last = new_weights.len() - 1;
+            new_weights[0] = end_weight;
+            new_weights[last] = end_weight;
+
+            return Some(new_weights);
+        }
+    };
+
+    let change = new_weights
+        .iter()
+        .zip(weights)
+        .map(|(a, b)| (*a - *b).powi(2))
+        .sum::<T>()
+        .sqrt();
+    let norm = weights.iter().map(|w| w.powi(2)).sum::<T>().sqrt();
+
+    if change <= tolerance * norm {
+        None
+    } else {
+        Some(new_weights)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::errors::WhittakerError;
+use crate::float::WhittakerFloat;
+
+/// The number of elements in each tile of series solved together by `smooth_block`. Small enough for a tile to stay in the L2
+/// cache through both substitutions.
+pub(crate) const BLOCK_TILE_SIZE: usize = 1 << 15;
+
+/// How a block of series is laid out in one contiguous slice.
+///
+/// The block is an `n × m` matrix holding `m` series of length `n`, one per column. The stride is the distance between the starts
+/// of consecutive rows or columns, so padded blocks and blocks within larger arrays can be read without copying them first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockLayout {
+    /// Rows are contiguous, so sample `i` of series `j` is at `i * stride + j`.
+    RowMajor {
+        /// The distance between the starts of consecutive rows. Must be at least the number of series.
+        stride: usize,
+    },
+    /// Columns are contiguous, so each series is contiguous and sample `i` of series `j` is at `j * stride + i`.
+    ColumnMajor {
+        /// The distance between the starts of consecutive columns. Must be at least the length of the series.
+        stride: usize,
+    },
+}
+
+impl BlockLayout {
+    /// The distances in the slice between consecutive samples of a series, and between the same sample of consecutive series.
+    pub(crate) fn steps(&self) -> (usize, usize) {
+        match self {
+            BlockLayout::RowMajor { stride } => (*stride, 1),
+            BlockLayout::ColumnMajor { stride } => (1, *stride),
+        }
+    }
+
+    /// The shortest slice that can hold `series` series of length `data_length`, checking that the stride is large enough.
+    pub(crate) fn required_length(
+        &self,
+        data_length: usize,
+        series: usize,
+    ) -> Result<usize, WhittakerError> {
+        let (stride, outer, inner) = self.shape(data_length, series);
+        if stride < inner {
+            return Err(WhittakerError::InvalidParameter("stride"));
+        }
+        if outer == 0 || inner == 0 {
+            return Ok(0);
+        }
+        Ok((outer - 1) * stride + inner)
+    }
+
+    /// The stride, and the number of strided and contiguous elements of the block.
+    fn shape(&self, data_length: usize, series: usize) -> (usize, usize, usize) {
+        match self {
+            BlockLayout::RowMajor { stride } => (*stride, data_length, series),
+            BlockLayout::ColumnMajor { stride } => (*stride, series, data_length),
+        }
+    }
+}
+
+/// Subtracts `scale` times row `source` from row `target` of a block stored row by row with `columns` values per row.
+pub(crate) fn subtract_row<T: WhittakerFloat>(
+    block: &mut [T],
+    columns: usize,
+    target: usize,
+    source: usize,
+    scale: T,
+) {
+    let (target_row, source_row) = if target > source {
+        let (head, tail) = block.split_at_mut(target * columns);
+        (
+            &mut tail[..columns],
+            &head[source * columns..(source + 1) * columns],
+        )
+    } else {
+        let (head, tail) = block.split_at_mut(source * columns);
+        (
+            &mut head[target * columns..(target + 1) * columns],
+            &tail[..columns],
+        )
+    };
+
+    for (value, source) in target_row.iter_mut().zip(source_row.iter()) {
+        *value -= scale * *source;
+    }
+}
+
+/// Divides each row of a block stored row by row with `columns` values per row by the matching element of `d`.
+pub(crate) fn divide_rows<T: WhittakerFloat>(block: &mut [T], columns: usize, d: &[T]) {
+    for (row, d) in block.chunks_exact_mut(columns).zip(d) {
+        for value in row {
+            *value /= *d;
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::float::WhittakerFloat;
+use sprs::binop::csmat_binop;
+use sprs::{vstack, CsMat, CsMatView};
+
+/// The boundary conditions at the ends of the data.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Boundary<T = f64> {
+    /// The ends of the data are free. Only differences between samples within the data are penalised.
+    #[default]
+    Open,
+    /// The ends of the data are neighbours, as for angles, day-of-year climatologies, and closed contours. The differences are
+    /// circulant, wrapping from the last sample back to the first, so the smoothed data has no break at the wrap point.
+    Periodic {
+        /// The distance in x after which the data repeats, so that the sample after the last lies at `x[0] + period`. Required with
+        /// an `x_input`, which must then span less than one period. Must be `None` for equally spaced data, which repeats after its
+        /// length.
+        period: Option<T>,
+    },
+}
+
+impl<T> Boundary<T> {
+    /// Whether the differences wrap around the ends of the data.
+    pub(crate) fn is_periodic(&self) -> bool {
+        matches!(self, Boundary::Periodic { .. })
+    }
+}
+
+/// Circulant dividing differencing matrix of order d.
+///
+/// Row `i` holds the divided difference of the `d + 1` samples from `i`, wrapping past the end of the data. For equally spaced data,
+/// `x_period` is `None` and the plain differences are used.
+///
+/// # Arguments
+/// * `x_period`: Sampling positions and the period after which they repeat.
+/// * `size`: Length of the data.
+/// * `d`: Order of differences.
+pub(crate) fn periodic_ddmat<T: WhittakerFloat>(
+    x_period: Option<(&[T], T)>,
+    size: usize,
+    d: usize,
+) -> CsMat<T> {
+    if d == 0 {
+        return CsMat::eye(size);
+    }
+
+    let differences = circular_diff(&periodic_ddmat(x_period, size, d - 1));
+
+    match x_period {
+        Some((x, period)) => {
+            let unwrapped = |j: usize| x[j % size] + period * T::from_usize(j / size).unwrap();
+            let dx: Vec<T> = (0..size)
+                .map(|i| T::one() / (unwrapped(i + d) - unwrapped(i)))
+                .collect();
+
+            let ind: Vec<usize> = (0..size + 1).collect();
+
+            let v = CsMatView::new((size, size), &ind, &ind[..size], &dx);
+
+            &v * &differences
+        }
+        None => differences,
+    }
+}
+
+/// Finds the difference between each row of a sparse matrix and the next, wrapping the last row around to the first.
+fn circular_diff<T: WhittakerFloat>(e: &CsMat<T>) -> CsMat<T> {
+    let rows = e.rows();
+    let shifted: CsMat<T> = vstack(&[e.slice_outer(1..rows), e.slice_outer(0..1)]);
+    csmat_binop(shifted.view(), e.view(), |a, b| *a - *b)
+}
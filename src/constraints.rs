@@ -0,0 +1,127 @@
+use crate::float::{cast, WhittakerFloat};
+
+/// The weight given to a point pinned to a bound, relative to the largest measurement weight.
+const BOUND_WEIGHT: f64 = 1e8;
+
+/// The weight given to a difference which breaks a shape constraint, relative to the largest measurement weight. Unlike pinning
+/// to a bound this couples neighbouring points, so it is kept smaller to limit cancellation when factorising.
+const SHAPE_WEIGHT: f64 = 1e6;
+
+/// The shape the smoothed data is constrained to.
+///
+/// Monotone constraints act on the first differences of the smoothed data and convexity constraints on the second differences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeConstraint {
+    /// The smoothed data never decreases.
+    MonotoneIncreasing,
+    /// The smoothed data never increases.
+    MonotoneDecreasing,
+    /// The smoothed data curves upwards, its second differences are never negative.
+    Convex,
+    /// The smoothed data curves downwards, its second differences are never positive.
+    Concave,
+}
+
+impl ShapeConstraint {
+    /// The order of the differences which are constrained.
+    pub(crate) fn difference_order(&self) -> usize {
+        match self {
+            ShapeConstraint::MonotoneIncreasing | ShapeConstraint::MonotoneDecreasing => 1,
+            ShapeConstraint::Convex | ShapeConstraint::Concave => 2,
+        }
+    }
+
+    /// The sign which makes the constrained differences non-negative when the constraint holds.
+    pub(crate) fn sign<T: WhittakerFloat>(&self) -> T {
+        match self {
+            ShapeConstraint::MonotoneIncreasing | ShapeConstraint::Convex => T::one(),
+            ShapeConstraint::MonotoneDecreasing | ShapeConstraint::Concave => -T::one(),
+        }
+    }
+}
+
+/// The result of smoothing with a shape constraint.
+#[derive(Clone, Debug)]
+pub struct ShapeSmoothResult<T = f64> {
+    /// The smoothed data.
+    pub smoothed: Vec<T>,
+    /// The number of iterations that were run.
+    pub iterations: usize,
+    /// Whether the set of penalised differences stopped changing before the maximum number of iterations was reached.
+    pub converged: bool,
+}
+
+/// The result of smoothing with bounds on the smoothed data.
+#[derive(Clone, Debug)]
+pub struct BoundedSmoothResult<T = f64> {
+    /// The smoothed data, which lies within the bounds.
+    pub smoothed: Vec<T>,
+    /// Whether each point ended up on its lower bound.
+    pub on_lower_bound: Vec<bool>,
+    /// Whether each point ended up on its upper bound.
+    pub on_upper_bound: Vec<bool>,
+    /// The number of iterations that were run.
+    pub iterations: usize,
+    /// Whether the set of points on a bound stopped changing before the maximum number of iterations was reached.
+    pub converged: bool,
+}
+
+/// Finds the points which should be pinned to a bound: those beyond it, and those already pinned which sit exactly on it. The latter
+/// can happen when pinning is accurate to within rounding. Points with no bound are never pinned.
+pub(crate) fn beyond_bound<T: WhittakerFloat>(
+    smoothed: &[T],
+    bound: Option<&[T]>,
+    pinned: &[bool],
+    is_beyond: impl Fn(T, T) -> bool,
+) -> Vec<bool> {
+    match bound {
+        Some(bound) => smoothed
+            .iter()
+            .zip(bound)
+            .zip(pinned)
+            .map(|((z, b), pinned)| is_beyond(*z, *b) || (*pinned && z == b))
+            .collect(),
+        None => vec![false; smoothed.len()],
+    }
+}
+
+/// Builds the weights and data which pin the points with a target to that target.
+///
+/// Each pinned point is given an additional large weight `κ` and its measurement is replaced by `(w y + κ b) / (w + κ)`, so that
+/// smoothing the returned data with the returned weights solves `(W + K + λD'D) z = W y + K b`.
+pub(crate) fn pinned<T: WhittakerFloat>(
+    y_input: &[T],
+    prior_weights: &[T],
+    targets: impl Iterator<Item = Option<T>>,
+) -> (Vec<T>, Vec<T>) {
+    let kappa = constraint_weight(prior_weights, BOUND_WEIGHT);
+
+    y_input
+        .iter()
+        .zip(prior_weights)
+        .zip(targets)
+        .map(|((y, w), target)| match target {
+            Some(target) => (*w + kappa, (*w * *y + kappa * target) / (*w + kappa)),
+            None => (*w, *y),
+        })
+        .unzip()
+}
+
+/// The weight which enforces the constraints on the differences which break a shape constraint.
+pub(crate) fn shape_weight<T: WhittakerFloat>(prior_weights: &[T]) -> T {
+    constraint_weight(prior_weights, SHAPE_WEIGHT)
+}
+
+/// Scales a relative constraint weight by the largest measurement weight, so constraints dominate however the data is weighted.
+fn constraint_weight<T: WhittakerFloat>(prior_weights: &[T], relative_weight: f64) -> T {
+    let largest_weight = prior_weights
+        .iter()
+        .fold(T::zero(), |largest, w| largest.max(*w));
+
+    cast::<T>(relative_weight)
+        * if largest_weight > T::zero() {
+            largest_weight
+        } else {
+            T::one()
+        }
+}
@@ -5,33 +5,213 @@
 /// provides the ability to return the optimal one.
 ///
 #[derive(Clone, Debug)]
-pub struct OptimisedSmoothResult {
+pub struct OptimisedSmoothResult<T = f64> {
     /// The lambda, smoothed series, and errors for each lambda tested.
-    pub validation_results: Vec<CrossValidationResult>,
+    pub validation_results: Vec<CrossValidationResult<T>>,
     pub(crate) optimal_index: usize,
 }
 
-impl OptimisedSmoothResult {
+impl<T: Clone> OptimisedSmoothResult<T> {
     /// Returns the optimally smoothed data series, lambda, and error.
-    pub fn get_optimal(&self) -> CrossValidationResult {
+    pub fn get_optimal(&self) -> CrossValidationResult<T> {
         self.validation_results[self.optimal_index].to_owned()
     }
 }
 /// The result of smoothing with cross validation
 #[derive(Clone, Debug)]
-pub struct CrossValidationResult {
+pub struct CrossValidationResult<T = f64> {
     /// The lambda value that was used to smooth the data.
-    pub lambda: f64,
+    pub lambda: T,
     /// The smoothed data.
-    pub smoothed: Vec<f64>,
+    pub smoothed: Vec<T>,
     /// The associated cross validation error for the smoothed data. Technically square-rooted cross validation error.
-    pub cross_validation_error: f64,
+    pub cross_validation_error: T,
+    /// The criterion used to score the smoothed data.
+    pub criterion: SelectionCriterion,
+    /// The score given by the criterion. Lower is better.
+    pub score: T,
+    /// The effective degrees of freedom of the fit, the trace of the hat matrix.
+    pub effective_degrees_of_freedom: T,
+}
+
+/// The criterion used to score a smoothed series when selecting lambda.
+///
+/// All criteria are minimised. Measurements with a weight of 0 are not counted as observations.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum SelectionCriterion {
+    /// Leave-one-out cross validation computed through the hat matrix, as in Eilers' 2003 paper. The score is the square-rooted
+    /// cross validation error.
+    #[default]
+    CrossValidation,
+    /// Generalized cross validation: `n·RSS / (n - edf)²`.
+    GeneralizedCrossValidation,
+    /// Corrected Akaike information criterion for smoothers: `ln(RSS / n) + 1 + 2(edf + 1) / (n - edf - 2)`.
+    Aicc,
+    /// Bayesian information criterion: `n·ln(RSS / n) + ln(n)·edf`. As `RSS` vanishes faster than `edf` grows when the fit approaches
+    /// interpolation, the lambdas searched should not extend to near-interpolating fits.
+    Bic,
+    /// Restricted (marginal) likelihood of the smoother viewed as a mixed model, given as minus twice the log-likelihood.
+    Reml,
+    /// Mallows' Cp: `RSS / σ² - n + 2·edf`. Contains the known noise variance σ².
+    MallowsCp(f64),
+    /// K-fold cross validation. Measurement `i` is held out in fold `i % k` by setting its weight to zero, so that it is interpolated
+    /// from the rest of the data. The score is the square-rooted weighted mean of the squared held-out errors. Contains `k`, which must
+    /// be at least 2. Each fold requires its own factorisation.
+    KFold(usize),
+    /// Blocked cross validation. Like `KFold`, but the data is split into the given number of contiguous blocks. As the neighbours of
+    /// held-out measurements are held out with them, it is much less prone to undersmoothing serially correlated data. Contains the
+    /// number of blocks, which must be at least 2.
+    BlockedCrossValidation(usize),
+}
+
+/// The quantities of a fit which the selection criteria are built from. These are always held in `f64`, whatever the smoother's float type.
+pub(crate) struct FitStatistics {
+    /// Weighted residual sum of squares.
+    pub residual_sum_of_squares: f64,
+    /// Trace of the hat matrix.
+    pub effective_degrees_of_freedom: f64,
+    /// Number of measurements with a non-zero weight.
+    pub observations: f64,
+    /// The roughness of the fit, `||V^½Dz||² + z'Pz / λ`, including any penalty weights and additional penalty terms.
+    pub penalty: f64,
+    /// `ln|W + λD'VD|`.
+    pub log_determinant: f64,
+    /// The sum of the logs of the penalty weights of each difference. Zero without penalty weights.
+    pub log_penalty_weights: f64,
+    /// The length of the data.
+    pub data_length: usize,
+    /// The number of independent components of the data left unpenalised. The order of the penalty for open boundaries.
+    pub null_space_dimension: usize,
+    /// The smoothing constant.
+    pub lambda: f64,
+}
+
+impl SelectionCriterion {
+    /// Scores a fit. Returns infinity where the criterion is undefined, for instance when the fit has no residual degrees of freedom.
+    ///
+    /// `cross_validation_error` is the leave-one-out error, or the held-out error for `KFold` and `BlockedCrossValidation`.
+    pub(crate) fn score(&self, cross_validation_error: f64, stats: &FitStatistics) -> f64 {
+        let n = stats.observations;
+        let rss = stats.residual_sum_of_squares;
+        let edf = stats.effective_degrees_of_freedom;
+
+        let score = match *self {
+            SelectionCriterion::CrossValidation
+            | SelectionCriterion::KFold(_)
+            | SelectionCriterion::BlockedCrossValidation(_) => cross_validation_error,
+            SelectionCriterion::GeneralizedCrossValidation => n * rss / (n - edf).powi(2),
+            SelectionCriterion::Aicc => {
+                if n - edf - 2.0 <= 0.0 {
+                    return f64::INFINITY;
+                }
+                (rss / n).ln() + 1.0 + 2.0 * (edf + 1.0) / (n - edf - 2.0)
+            }
+            SelectionCriterion::Bic => {
+                if rss <= 0.0 {
+                    return f64::INFINITY;
+                }
+                n * (rss / n).ln() + n.ln() * edf
+            }
+            SelectionCriterion::Reml => {
+                let residual_dof = n - stats.null_space_dimension as f64;
+                if residual_dof <= 0.0 {
+                    return f64::INFINITY;
+                }
+                let variance = (rss + stats.lambda * stats.penalty) / residual_dof;
+
+                residual_dof * variance.ln() + stats.log_determinant
+                    - (stats.data_length - stats.null_space_dimension) as f64 * stats.lambda.ln()
+                    - stats.log_penalty_weights
+            }
+            SelectionCriterion::MallowsCp(variance) => rss / variance - n + 2.0 * edf,
+        };
+
+        if score.is_nan() {
+            f64::INFINITY
+        } else {
+            score
+        }
+    }
+}
+
+/// Options for selecting the optimal lambda with [crate::WhittakerSmoother::smooth_optimal_with_options].
+///
+/// The defaults match `smooth_optimal` with `break_serial_correlation = true`: lambdas from 1e-5 to 1e8 in logarithmic steps of 0.5, ranked
+/// by leave-one-out cross validation on every fifth measurement.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct SmoothOptimalOptions {
+    /// The lambdas to test.
+    pub lambdas: LambdaSearch,
+    /// The criterion used to rank each lambda.
+    pub criterion: SelectionCriterion,
+    /// How serial correlation in the data is broken while selecting lambda.
+    pub serial_correlation: SerialCorrelation,
+}
+
+/// The lambdas tested when selecting the optimal lambda.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LambdaSearch {
+    /// Tests each of the given lambdas. All must be positive.
+    Grid(Vec<f64>),
+    /// Tests `steps` lambdas spaced logarithmically from `min` to `max`, inclusive.
+    Range {
+        /// The smallest lambda to test.
+        min: f64,
+        /// The largest lambda to test.
+        max: f64,
+        /// The number of lambdas to test. Must be at least 2.
+        steps: usize,
+    },
+    /// Searches continuously in log10(lambda) with Brent's method.
+    Brent {
+        /// The smallest and largest lambda to search between.
+        bounds: (f64, f64),
+        /// How precisely to locate the optimal lambda, in decades.
+        tolerance: f64,
+        /// The maximum number of lambdas to evaluate.
+        max_evaluations: usize,
+    },
+}
+
+impl Default for LambdaSearch {
+    fn default() -> Self {
+        LambdaSearch::Range {
+            min: 1e-5,
+            max: 1e8,
+            steps: 27,
+        }
+    }
+}
+
+/// How serial correlation in the data is broken while selecting lambda.
+///
+/// Criteria computed from the fit itself assume independent errors. With serially correlated data, they favour lambdas which barely
+/// smooth the data at all.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SerialCorrelation {
+    /// Uses all of the data as is.
+    Ignore,
+    /// Selects lambda using every n-th measurement, before smoothing the full data with it. Contains n, which must be positive.
+    Decimate(usize),
+    /// Selects lambda using only the measurements whose mask value is `true`, before smoothing the full data with it. The mask must be
+    /// as long as the data.
+    IndexMask(Vec<bool>),
+    /// Uses all of the data, but ignores the criterion and selects lambda with [SelectionCriterion::BlockedCrossValidation] instead.
+    /// Contains the number of blocks, which must be at least 2.
+    BlockCrossValidation(usize),
+}
+
+impl Default for SerialCorrelation {
+    fn default() -> Self {
+        SerialCorrelation::Decimate(5)
+    }
 }
 
-pub(crate) fn every_fifth_element(data: &[f64]) -> Vec<f64> {
+/// Keeps the elements of the data whose mask value is `true`.
+pub(crate) fn select<T: Copy>(data: &[T], mask: &[bool]) -> Vec<T> {
     data.iter()
-        .enumerate()
-        .filter(|(index, _)| index % 5 == 0)
-        .map(|(_, val)| *val)
-        .collect::<Vec<f64>>()
+        .zip(mask)
+        .filter(|(_, keep)| **keep)
+        .map(|(val, _)| *val)
+        .collect::<Vec<T>>()
 }
@@ -0,0 +1,111 @@
+use crate::boundary::{periodic_ddmat, Boundary};
+use crate::float::{cast, WhittakerFloat};
+use crate::whittaker_smoother::{ddmat, diff_no_ddmat};
+use sprs::CsMat;
+
+/// Estimates a derivative of the smoothed data at every sample position.
+///
+/// The `k`-th divided differences of the data, built by [ddmat] for unequally spaced data, estimate the `k`-th derivative divided
+/// by `k!` at the centre of the `k + 1` positions they span. For equally spaced data the plain differences are used, which are the
+/// derivative with respect to the sample index. The differences are then linearly interpolated from their centres back to the sample
+/// positions. At the boundaries, where there are no centres on one side, the two nearest differences are linearly extrapolated. With
+/// periodic boundaries the circulant differences are used instead, and are interpolated across the wrap point.
+pub(crate) fn derivative<T: WhittakerFloat>(
+    smoothed: &[T],
+    x_input: Option<&[T]>,
+    derivative_order: usize,
+    boundary: &Boundary<T>,
+) -> Vec<T> {
+    let n = smoothed.len();
+    if derivative_order == 0 {
+        return smoothed.to_vec();
+    }
+
+    let positions = match x_input {
+        Some(x) => x.to_vec(),
+        None => (0..n).map(|i| cast(i as f64)).collect::<Vec<T>>(),
+    };
+
+    let d_mat = match (boundary, x_input) {
+        (Boundary::Periodic { period }, x) => periodic_ddmat(x.zip(*period), n, derivative_order),
+        (Boundary::Open, Some(x)) => ddmat(x, n, derivative_order),
+        (Boundary::Open, None) => diff_no_ddmat(&CsMat::eye(n), derivative_order),
+    };
+    let scale = match x_input {
+        Some(_) => cast::<T>((1..=derivative_order).map(|k| k as f64).product::<f64>()),
+        None => T::one(),
+    };
+
+    let mut differences = vec![T::zero(); d_mat.rows()];
+    for (value, (row, column)) in d_mat.iter() {
+        differences[row] += *value * smoothed[column];
+    }
+    for difference in differences.iter_mut() {
+        *difference *= scale;
+    }
+
+    if let Boundary::Periodic { period } = boundary {
+        let period = period.unwrap_or_else(|| cast(n as f64));
+        return interpolate_periodic(&positions, differences, period, derivative_order);
+    }
+
+    let span = cast::<T>((derivative_order + 1) as f64);
+    let centres = positions
+        .windows(derivative_order + 1)
+        .map(|window| window.iter().copied().sum::<T>() / span)
+        .collect::<Vec<T>>();
+
+    if centres.len() == 1 {
+        return vec![differences[0]; n];
+    }
+
+    let mut segment = 0;
+    positions
+        .iter()
+        .map(|position| {
+            while segment + 2 < centres.len() && centres[segment + 1] < *position {
+                segment += 1;
+            }
+            let fraction =
+                (*position - centres[segment]) / (centres[segment + 1] - centres[segment]);
+            differences[segment] + fraction * (differences[segment + 1] - differences[segment])
+        })
+        .collect()
+}
+
+/// Interpolates circulant differences back to the sample positions.
+///
+/// Row `i` of the circulant differences spans the `k + 1` samples from `i`, wrapping past the end of the data, so its centre is taken
+/// over their positions unwrapped by the period. The centres go once around the period, and the first of them follows the last.
+fn interpolate_periodic<T: WhittakerFloat>(
+    positions: &[T],
+    mut differences: Vec<T>,
+    period: T,
+    derivative_order: usize,
+) -> Vec<T> {
+    let n = positions.len();
+    let span = cast::<T>((derivative_order + 1) as f64);
+    let unwrapped = |j: usize| positions[j % n] + period * cast::<T>((j / n) as f64);
+
+    let mut centres = (0..n)
+        .map(|i| (i..=i + derivative_order).map(unwrapped).sum::<T>() / span)
+        .collect::<Vec<T>>();
+    centres.push(centres[0] + period);
+    differences.push(differences[0]);
+
+    positions
+        .iter()
+        .map(|position| {
+            // Positions before the first centre lie between the last centre and the wrapped first one.
+            let position = if *position < centres[0] {
+                *position + period
+            } else {
+                *position
+            };
+            let segment = centres.partition_point(|centre| *centre <= position) - 1;
+            let fraction =
+                (position - centres[segment]) / (centres[segment + 1] - centres[segment]);
+            differences[segment] + fraction * (differences[segment + 1] - differences[segment])
+        })
+        .collect()
+}
@@ -20,8 +20,15 @@ pub enum WhittakerError {
     SampleRateError(usize),
     /// Occurs when the x input is not increasing Monotonically. It should be always increasing; never remaining constant or decreasing. Contains the offending data index.
     NotMonotonicallyIncreasing(usize),
-    /// Occurs when trying to invert a matrix that cannot be inverted. This can only occur when computing cross validation error.
-    MatrixNotInvertible
+    /// No longer returned. Cross validation used to invert a matrix, but the hat matrix's diagonal is now taken from the LDL factor,
+    /// whose failures are reported as [WhittakerError::SolverError]. Kept so that matching on it still compiles.
+    MatrixNotInvertible,
+    /// Occurs when a parameter falls outside of its valid range. Contains the name of the offending parameter.
+    InvalidParameter(&'static str),
+    /// Occurs when an element of an input is NaN, infinite, or outside of its valid range, such as a negative weight. Contains the name of the offending parameter and the offending index.
+    InvalidValue(&'static str, usize),
+    /// Occurs when every measurement has a weight of zero, leaving nothing to smooth.
+    AllWeightsZero,
 }
 
 impl std::fmt::Display for WhittakerError {
@@ -55,6 +62,15 @@ impl std::fmt::Display for WhittakerError {
             ),
             WhittakerError::MatrixNotInvertible => write!(
                 f, "When computing cross validation, a matrix inversion is computed. Your current data is unable to be inverted."
+            ),
+            WhittakerError::InvalidParameter(name) => write!(
+                f, "Parameter `{}` is outside of its valid range.", name
+            ),
+            WhittakerError::InvalidValue(name, position) => write!(
+                f, "Parameter `{}` contains a value that is not finite or is outside of its valid range. Offending index: {}.", name, position
+            ),
+            WhittakerError::AllWeightsZero => write!(
+                f, "All weights are zero. At least one measurement needs a positive weight to be smoothed."
             )
         }
     }
@@ -0,0 +1,40 @@
+use num_traits::{Float, FromPrimitive, NumAssign};
+use sprs::MulAcc;
+use std::fmt::Debug;
+use std::iter::Sum;
+
+/// The floating point types that the [crate::WhittakerSmoother] can work with: `f32` and `f64`.
+///
+/// The smoother, its inputs, and its results all share the same float type, so `f32` data can be smoothed without converting it.
+/// `f64` is the default and should be preferred unless memory or bandwidth matters more than precision. The system solved by the
+/// smoother becomes badly conditioned as lambda grows, so with `f32` very large lambdas (above ~1e6 for an order of 2) lose accuracy
+/// and may fail to factorise. The default lambdas tested by `smooth_optimal` reach 1e8, so with `f32` use
+/// [crate::SmoothOptimalOptions] to search a smaller range.
+pub trait WhittakerFloat:
+    Float
+    + FromPrimitive
+    + NumAssign
+    + Sum
+    + for<'a> Sum<&'a Self>
+    + for<'a> std::ops::DivAssign<&'a Self>
+    + MulAcc
+    + Default
+    + Debug
+    + Send
+    + Sync
+    + 'static
+{
+}
+
+impl WhittakerFloat for f32 {}
+impl WhittakerFloat for f64 {}
+
+/// Converts an `f64` constant or parameter into the smoother's float type.
+pub(crate) fn cast<T: WhittakerFloat>(value: f64) -> T {
+    T::from_f64(value).unwrap_or_else(T::nan)
+}
+
+/// Converts a value back to `f64`, for the statistics which are always computed in double precision.
+pub(crate) fn to_f64<T: WhittakerFloat>(value: T) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
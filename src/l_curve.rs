@@ -0,0 +1,105 @@
+use crate::float::WhittakerFloat;
+
+/// The method used to find the corner of the L-curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CornerMethod {
+    /// The point of maximum curvature of the L-curve, as in Hansen's L-curve criterion. Requires at least 3 lambdas.
+    #[default]
+    MaximumCurvature,
+    /// Eilers' V-curve. The distance between successive points of the L-curve is minimised and lambda is taken as the
+    /// geometric mean of the two points. Requires at least 2 lambdas.
+    VCurve,
+}
+
+/// A single point of the L-curve.
+#[derive(Clone, Debug)]
+pub struct LCurvePoint<T = f64> {
+    /// The lambda value that was used to smooth the data.
+    pub lambda: T,
+    /// The smoothed data.
+    pub smoothed: Vec<T>,
+    /// The weighted norm of the residuals, `||W^½(y - z)||`.
+    pub residual_norm: T,
+    /// The square root of the full penalty divided by lambda, `sqrt(||V^½Dz||² + z'Pz / λ)`, including any penalty weights `V` and
+    /// additional penalty terms `P`. Without either, this is the roughness norm `||Dz||`.
+    pub penalty_norm: T,
+}
+
+/// The result of sweeping lambda to trace out the L-curve.
+#[derive(Clone, Debug)]
+pub struct LCurveResult<T = f64> {
+    /// The residual and penalty norms for each lambda tested.
+    pub points: Vec<LCurvePoint<T>>,
+    /// The corner of the curve and the lambda chosen by it.
+    pub corner: LCurvePoint<T>,
+}
+
+/// Finds the index of the point of maximum curvature of the L-curve in log-log space.
+///
+/// Both log norms are treated as functions of log10(lambda) and differentiated with three-point finite differences, which allows
+/// unequally spaced lambdas. The end points have no curvature estimate and are never chosen.
+pub(crate) fn maximum_curvature_index<T: WhittakerFloat>(points: &[LCurvePoint<T>]) -> usize {
+    let t = points.iter().map(|p| log10(p.lambda)).collect::<Vec<f64>>();
+    let xi = points
+        .iter()
+        .map(|p| log10(p.residual_norm))
+        .collect::<Vec<f64>>();
+    let eta = points
+        .iter()
+        .map(|p| log10(p.penalty_norm))
+        .collect::<Vec<f64>>();
+
+    let derivatives = |f: &[f64], i: usize| -> (f64, f64) {
+        let (h0, h1) = (t[i] - t[i - 1], t[i + 1] - t[i]);
+        let first = (h0 * h0 * f[i + 1] - h1 * h1 * f[i - 1] + (h1 * h1 - h0 * h0) * f[i])
+            / (h0 * h1 * (h0 + h1));
+        let second =
+            2.0 * (h0 * f[i + 1] - (h0 + h1) * f[i] + h1 * f[i - 1]) / (h0 * h1 * (h0 + h1));
+        (first, second)
+    };
+
+    (1..points.len() - 1)
+        .map(|i| {
+            let (xi_1, xi_2) = derivatives(&xi, i);
+            let (eta_1, eta_2) = derivatives(&eta, i);
+            let curvature = (xi_1 * eta_2 - xi_2 * eta_1) / (xi_1 * xi_1 + eta_1 * eta_1).powf(1.5);
+            (
+                i,
+                if curvature.is_nan() {
+                    f64::NEG_INFINITY
+                } else {
+                    curvature
+                },
+            )
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Finds the index of the first point of the shortest segment between successive points of the L-curve in log-log space.
+pub(crate) fn v_curve_index<T: WhittakerFloat>(points: &[LCurvePoint<T>]) -> usize {
+    points
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let distance = (log10(pair[1].residual_norm) - log10(pair[0].residual_norm))
+                .hypot(log10(pair[1].penalty_norm) - log10(pair[0].penalty_norm));
+            (
+                i,
+                if distance.is_nan() {
+                    f64::INFINITY
+                } else {
+                    distance
+                },
+            )
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Takes the base 10 logarithm in `f64`, whatever the smoother's float type.
+fn log10<T: WhittakerFloat>(value: T) -> f64 {
+    value.to_f64().map_or(f64::NAN, f64::log10)
+}
@@ -1,11 +1,46 @@
 #![doc = include_str!("../README.md")]
 #![deny(missing_docs, unused_imports)]
 
+mod adaptive;
+#[cfg(feature = "ndarray")]
+mod array;
+mod banded;
+mod baseline;
+mod block;
+mod boundary;
+mod confidence_bands;
+mod constraints;
 mod cross_validation;
+mod derivative;
 mod errors;
+mod float;
+mod l_curve;
+mod missing;
+mod optimise;
+mod penalty;
+mod prediction;
+mod robust;
+mod selected_inversion;
 mod whittaker_smoother;
+mod whittaker_smoother_2d;
 
-pub use cross_validation::{CrossValidationResult, OptimisedSmoothResult};
+pub use adaptive::AdaptiveSmoothResult;
+pub use baseline::{BaselineMethod, BaselineResult};
+pub use block::BlockLayout;
+pub use boundary::Boundary;
+pub use confidence_bands::ConfidenceBandResult;
+pub use constraints::{BoundedSmoothResult, ShapeConstraint, ShapeSmoothResult};
+pub use cross_validation::{
+    CrossValidationResult, LambdaSearch, OptimisedSmoothResult, SelectionCriterion,
+    SerialCorrelation, SmoothOptimalOptions,
+};
 pub use errors::WhittakerError;
 pub use errors::WHITTAKER_X_EPSILON;
+pub use float::WhittakerFloat;
+pub use l_curve::{CornerMethod, LCurvePoint, LCurveResult};
+pub use missing::MissingSmoothResult;
+pub use penalty::PenaltyTerm;
+pub use prediction::PredictionResult;
+pub use robust::{RobustLoss, RobustSmoothResult};
 pub use whittaker_smoother::WhittakerSmoother;
+pub use whittaker_smoother_2d::WhittakerSmoother2D;
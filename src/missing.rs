@@ -0,0 +1,29 @@
+use crate::float::WhittakerFloat;
+
+/// The result of smoothing data with missing values.
+#[derive(Clone, Debug)]
+pub struct MissingSmoothResult<T = f64> {
+    /// The smoothed data, with the missing samples interpolated.
+    pub smoothed: Vec<T>,
+    /// Whether each sample was missing, and so imputed by the smoothing rather than smoothed.
+    pub imputed: Vec<bool>,
+}
+
+/// Marks the samples which are missing, given as NaN or another non-finite value.
+pub(crate) fn missing_mask<T: WhittakerFloat>(y_input: &[T]) -> Vec<bool> {
+    y_input.iter().map(|y| !y.is_finite()).collect()
+}
+
+/// Merges the user's weights, or equal weights if there are none, with zero weights for the missing samples.
+pub(crate) fn missing_weights<T: WhittakerFloat>(
+    weights: Option<Vec<T>>,
+    missing: &[bool],
+) -> Vec<T> {
+    let weights = weights.unwrap_or_else(|| vec![T::one(); missing.len()]);
+
+    weights
+        .into_iter()
+        .zip(missing)
+        .map(|(weight, missing)| if *missing { T::zero() } else { weight })
+        .collect()
+}
@@ -0,0 +1,94 @@
+/// Minimises a function of one variable on the interval `[lower, upper]` with Brent's method.
+///
+/// Combines golden-section search with successive parabolic interpolation, as in Brent's 1973 "Algorithms for Minimization without
+/// Derivatives". Stops once the minimum is located to within `tolerance` or after `max_evaluations` calls to `f`. The caller records
+/// the evaluations it needs, so nothing is returned.
+pub(crate) fn brent_minimise<E>(
+    mut f: impl FnMut(f64) -> Result<f64, E>,
+    lower: f64,
+    upper: f64,
+    tolerance: f64,
+    max_evaluations: usize,
+) -> Result<(), E> {
+    let golden_ratio = 0.5 * (3.0 - 5.0_f64.sqrt());
+
+    let (mut a, mut b) = (lower, upper);
+    let mut x = a + golden_ratio * (b - a);
+    let (mut v, mut w) = (x, x);
+    let (mut d, mut e) = (0.0_f64, 0.0_f64);
+
+    let mut fx = f(x)?;
+    let (mut fv, mut fw) = (fx, fx);
+    let mut evaluations = 1;
+
+    while evaluations < max_evaluations {
+        let middle = 0.5 * (a + b);
+        let tol1 = f64::EPSILON.sqrt() * x.abs() + tolerance / 3.0;
+        let tol2 = 2.0 * tol1;
+
+        if (x - middle).abs() <= tol2 - 0.5 * (b - a) {
+            break;
+        }
+
+        let mut golden_step = true;
+        if e.abs() > tol1 {
+            // Fit a parabola through x, v, and w.
+            let r = (x - w) * (fx - fv);
+            let mut q = (x - v) * (fx - fw);
+            let mut p = (x - v) * q - (x - w) * r;
+            q = 2.0 * (q - r);
+            if q > 0.0 {
+                p = -p;
+            } else {
+                q = -q;
+            }
+            let previous_e = e;
+            e = d;
+
+            if p.abs() < (0.5 * q * previous_e).abs() && p > q * (a - x) && p < q * (b - x) {
+                d = p / q;
+                let u = x + d;
+                if u - a < tol2 || b - u < tol2 {
+                    d = if x < middle { tol1 } else { -tol1 };
+                }
+                golden_step = false;
+            }
+        }
+        if golden_step {
+            e = if x < middle { b - x } else { a - x };
+            d = golden_ratio * e;
+        }
+
+        let u = if d.abs() >= tol1 {
+            x + d
+        } else {
+            x + tol1.copysign(d)
+        };
+        let fu = f(u)?;
+        evaluations += 1;
+
+        if fu <= fx {
+            if u < x {
+                b = x;
+            } else {
+                a = x;
+            }
+            (v, fv) = (w, fw);
+            (w, fw) = (x, fx);
+            (x, fx) = (u, fu);
+        } else {
+            if u < x {
+                a = u;
+            } else {
+                b = u;
+            }
+            if fu <= fw || w == x {
+                (v, fv) = (w, fw);
+                (w, fw) = (u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv) = (u, fu);
+            }
+        }
+    }
+    Ok(())
+}
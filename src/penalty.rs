@@ -0,0 +1,92 @@
+use crate::errors::WhittakerError;
+use crate::float::{cast, WhittakerFloat};
+use crate::whittaker_smoother::{difference_matrix, validate_lambda};
+use crate::Boundary;
+use sprs::binop::csmat_binop;
+use sprs::CsMat;
+
+/// A term of the roughness penalty of a [crate::WhittakerSmoother].
+///
+/// The smoother penalises the sum of its terms, so combining a first and a second order difference term damps both the slope and the
+/// curvature of the smoothed data.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PenaltyTerm<T = f64> {
+    /// Penalises the differences of the given order, `λD'D`. Divided differences are used for unequally spaced data, and the
+    /// differences follow the smoother's [Boundary].
+    Difference {
+        /// The order of the differences.
+        order: usize,
+        /// The smoothing strength of the term.
+        lambda: T,
+    },
+    /// Penalises with a user-supplied matrix `P`, as `λP`. The matrix must be square, as long as the data, symmetric, and positive
+    /// semi-definite. A harmonic penalty, for example, smooths seasonal series towards a sinusoid of a given period.
+    Matrix {
+        /// The penalty matrix.
+        matrix: CsMat<T>,
+        /// The smoothing strength of the term.
+        lambda: T,
+    },
+}
+
+impl<T: WhittakerFloat> PenaltyTerm<T> {
+    /// The smoothing strength of the term.
+    pub fn lambda(&self) -> T {
+        match self {
+            PenaltyTerm::Difference { lambda, .. } | PenaltyTerm::Matrix { lambda, .. } => *lambda,
+        }
+    }
+}
+
+/// Sums the penalty terms, each scaled by its lambda, into a single matrix. Returns `None` when there are no terms.
+pub(crate) fn sum_penalties<T: WhittakerFloat>(
+    terms: &[PenaltyTerm<T>],
+    data_length: usize,
+    x_input: Option<&[T]>,
+    boundary: &Boundary<T>,
+) -> Result<Option<CsMat<T>>, WhittakerError> {
+    let mut total: Option<CsMat<T>> = None;
+
+    for term in terms {
+        validate_lambda(term.lambda())?;
+        let penalty = match term {
+            PenaltyTerm::Difference { order, lambda } => {
+                if data_length < *order {
+                    return Err(WhittakerError::DataTooShort(data_length, *order));
+                }
+                let d_mat = difference_matrix(x_input, data_length, *order, boundary);
+                (&d_mat.transpose_view() * &d_mat).map(|value| *value * *lambda)
+            }
+            PenaltyTerm::Matrix { matrix, lambda } => {
+                if matrix.rows() != data_length {
+                    return Err(WhittakerError::LengthMismatch(data_length, matrix.rows()));
+                }
+                if matrix.cols() != data_length {
+                    return Err(WhittakerError::LengthMismatch(data_length, matrix.cols()));
+                }
+                if !is_symmetric(matrix) {
+                    return Err(WhittakerError::InvalidParameter("penalties"));
+                }
+                matrix.to_csr().map(|value| *value * *lambda)
+            }
+        };
+
+        total = Some(match total {
+            Some(total) => csmat_binop(total.view(), penalty.view(), |a, b| *a + *b),
+            None => penalty,
+        });
+    }
+
+    Ok(total)
+}
+
+/// Checks that a matrix is finite and equal to its transpose, to within rounding.
+fn is_symmetric<T: WhittakerFloat>(matrix: &CsMat<T>) -> bool {
+    let tolerance = cast::<T>(1e3) * T::epsilon();
+
+    matrix.iter().all(|(value, (row, column))| {
+        let mirrored = matrix.get(column, row).copied().unwrap_or_else(T::zero);
+        value.is_finite()
+            && (*value - mirrored).abs() <= tolerance * value.abs().max(mirrored.abs())
+    })
+}
@@ -0,0 +1,68 @@
+use crate::float::{cast, WhittakerFloat};
+use crate::WHITTAKER_X_EPSILON;
+
+/// The result of evaluating the smoothed curve at new positions.
+#[derive(Clone, Debug)]
+pub struct PredictionResult<T = f64> {
+    /// The smoothed curve at each query position, in the order the positions were given.
+    pub predicted: Vec<T>,
+    /// The smoothed data at the original sample positions.
+    pub smoothed: Vec<T>,
+}
+
+/// The sample positions merged with the query positions.
+pub(crate) struct MergedPositions<T> {
+    /// The sorted, merged positions.
+    pub(crate) positions: Vec<T>,
+    /// Whether each merged position is an original sample.
+    pub(crate) is_sample: Vec<bool>,
+    /// The index within the merged positions of each query, in the order the queries were given.
+    pub(crate) query_indices: Vec<usize>,
+}
+
+/// Merges query positions into the sorted sample positions.
+///
+/// A query closer than [WHITTAKER_X_EPSILON] to a sample, or to an earlier query, shares its position rather than being inserted,
+/// so the merged positions remain valid sample positions for the smoother. The queries must be finite.
+pub(crate) fn merge_positions<T: WhittakerFloat>(
+    x_input: &[T],
+    x_query: &[T],
+) -> MergedPositions<T> {
+    let epsilon = cast::<T>(WHITTAKER_X_EPSILON);
+
+    let mut query_order = (0..x_query.len()).collect::<Vec<usize>>();
+    query_order.sort_by(|a, b| x_query[*a].partial_cmp(&x_query[*b]).unwrap());
+
+    let mut positions = Vec::with_capacity(x_input.len() + x_query.len());
+    let mut is_sample = Vec::with_capacity(x_input.len() + x_query.len());
+    let mut query_indices = vec![0; x_query.len()];
+    let mut next_sample = 0;
+
+    for query in query_order {
+        let value = x_query[query];
+
+        while next_sample < x_input.len() && x_input[next_sample] < value - epsilon {
+            positions.push(x_input[next_sample]);
+            is_sample.push(true);
+            next_sample += 1;
+        }
+        if next_sample < x_input.len() && (x_input[next_sample] - value).abs() < epsilon {
+            positions.push(x_input[next_sample]);
+            is_sample.push(true);
+            next_sample += 1;
+        } else if positions.last().is_none_or(|last| value - *last >= epsilon) {
+            positions.push(value);
+            is_sample.push(false);
+        }
+        query_indices[query] = positions.len() - 1;
+    }
+
+    positions.extend_from_slice(&x_input[next_sample..]);
+    is_sample.resize(positions.len(), true);
+
+    MergedPositions {
+        positions,
+        is_sample,
+        query_indices,
+    }
+}
@@ -0,0 +1,110 @@
+use crate::float::{cast, WhittakerFloat};
+use std::cmp::Ordering;
+
+/// The loss function used to downweight outliers when robustly smoothing.
+///
+/// Each variant contains its tuning constant which is applied to residuals scaled by a robust estimate of their
+/// spread (the median absolute deviation). Smaller constants reject more points as outliers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RobustLoss {
+    /// Huber loss. Residuals beyond the tuning constant are downweighted in proportion to their size. 1.345 is a good default.
+    Huber(f64),
+    /// Tukey's biweight. Residuals beyond the tuning constant are given zero weight and are therefore interpolated. 4.685 is a good default.
+    Tukey(f64),
+    /// Cauchy loss. Every residual is downweighted smoothly, without ever reaching zero. 2.385 is a good default.
+    Cauchy(f64),
+}
+
+impl Default for RobustLoss {
+    fn default() -> Self {
+        RobustLoss::Tukey(4.685)
+    }
+}
+
+impl RobustLoss {
+    /// The tuning constant contained in the loss.
+    pub(crate) fn tuning_constant(&self) -> f64 {
+        match *self {
+            RobustLoss::Huber(k) => k,
+            RobustLoss::Tukey(c) | RobustLoss::Cauchy(c) => c,
+        }
+    }
+
+    /// Weight given to a residual which has already been divided by the residual scale.
+    pub(crate) fn weight<T: WhittakerFloat>(&self, scaled_residual: T) -> T {
+        let u = scaled_residual.abs();
+        match *self {
+            RobustLoss::Huber(k) => {
+                let k = cast::<T>(k);
+                if u <= k {
+                    T::one()
+                } else {
+                    k / u
+                }
+            }
+            RobustLoss::Tukey(c) => {
+                let c = cast::<T>(c);
+                if u < c {
+                    (T::one() - (u / c).powi(2)).powi(2)
+                } else {
+                    T::zero()
+                }
+            }
+            RobustLoss::Cauchy(c) => T::one() / (T::one() + (u / cast::<T>(c)).powi(2)),
+        }
+    }
+}
+
+/// The result of robustly smoothing a data series.
+#[derive(Clone, Debug)]
+pub struct RobustSmoothResult<T = f64> {
+    /// The smoothed data.
+    pub smoothed: Vec<T>,
+    /// The robust weight of each measurement after the final iteration. Values close to 0 indicate outliers.
+    /// These do not include any weights the smoother was configured with.
+    pub robust_weights: Vec<T>,
+    /// The number of reweighting iterations that were run.
+    pub iterations: usize,
+    /// Whether the smoothed series stopped changing by more than the tolerance before the maximum number of iterations was reached.
+    pub converged: bool,
+}
+
+/// Computes the robust weight of each residual. Measurements with a prior weight of 0 are ignored when estimating the scale.
+pub(crate) fn robust_weights<T: WhittakerFloat>(
+    residuals: &[T],
+    prior_weights: &[T],
+    loss: RobustLoss,
+) -> Vec<T> {
+    let mut absolute_residuals = residuals
+        .iter()
+        .zip(prior_weights)
+        .filter(|(_, weight)| **weight > T::zero())
+        .map(|(residual, _)| residual.abs())
+        .collect::<Vec<T>>();
+
+    // 0.6745 makes the median absolute deviation a consistent estimator of the standard deviation for normal errors.
+    let scale = median(&mut absolute_residuals) / cast(0.6745);
+
+    if scale <= T::epsilon() {
+        return vec![T::one(); residuals.len()];
+    }
+
+    residuals
+        .iter()
+        .map(|residual| loss.weight(*residual / scale))
+        .collect()
+}
+
+pub(crate) fn median<T: WhittakerFloat>(data: &mut [T]) -> T {
+    if data.is_empty() {
+        return T::zero();
+    }
+    data.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let middle = data.len() / 2;
+    if data.len().is_multiple_of(2) {
+        (data[middle - 1] + data[middle]) / cast(2.0)
+    } else {
+        data[middle]
+    }
+}
@@ -0,0 +1,73 @@
+use crate::float::WhittakerFloat;
+use sprs::PermOwned;
+use sprs_ldl::LdlNumeric;
+
+/// Computes the diagonal of `A^-1` from the LDLᵀ factorisation of `P A Pᵀ`.
+///
+/// Uses the Takahashi recurrences which only visit the elements of the inverse that fall within the sparsity pattern of `L`. Working
+/// backwards through the columns of `L`,
+///
+/// `Z_ij = -Σ_k L_kj Z_ik` and `Z_jj = 1 / d_j - Σ_k L_kj Z_kj`
+///
+/// where the sums run over the non-zero rows `k` of column `j`. Every `Z_ik` required has already been computed as the pattern of a
+/// Cholesky factor is closed under these recurrences. For the banded systems produced by the smoother, this takes `O(n·d²)` time
+/// rather than the `O(n³)` of a dense inverse.
+pub(crate) fn inverse_diagonal<T: WhittakerFloat>(
+    ldl: &LdlNumeric<T, usize>,
+    perm: &PermOwned,
+) -> Vec<T> {
+    let l = ldl.l();
+    let d = ldl.d();
+    let n = d.len();
+
+    let indptr = l.indptr();
+    let indptr = indptr.raw_storage();
+    let indices = l.indices();
+    let l_data = l.data();
+
+    // The strictly lower triangle of Z, stored in the same pattern as L.
+    let mut z_data = vec![T::zero(); l_data.len()];
+    let mut z_diagonal = vec![T::zero(); n];
+
+    let z_lookup = |z_data: &[T], z_diagonal: &[T], row: usize, column: usize| -> T {
+        if row == column {
+            return z_diagonal[row];
+        }
+        let (row, column) = if row > column {
+            (row, column)
+        } else {
+            (column, row)
+        };
+        let start = indptr[column];
+        let end = indptr[column + 1];
+
+        match indices[start..end].binary_search(&row) {
+            Ok(position) => z_data[start + position],
+            Err(_) => T::zero(),
+        }
+    };
+
+    for j in (0..n).rev() {
+        let start = indptr[j];
+        let end = indptr[j + 1];
+
+        for a in (start..end).rev() {
+            let i = indices[a];
+            let value = -(start..end)
+                .map(|b| l_data[b] * z_lookup(&z_data, &z_diagonal, i, indices[b]))
+                .sum::<T>();
+            z_data[a] = value;
+        }
+
+        z_diagonal[j] = T::one() / d[j]
+            - (start..end)
+                .map(|b| l_data[b] * z_data[b])
+                .sum::<T>();
+    }
+
+    let mut diagonal = vec![T::zero(); n];
+    for (i, value) in z_diagonal.into_iter().enumerate() {
+        diagonal[perm.at(i)] = value;
+    }
+    diagonal
+}
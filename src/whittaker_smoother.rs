@@ -1,35 +1,89 @@
-use crate::cross_validation::every_fifth_element;
+use crate::adaptive::{difference_weights, roughness_penalty_weights};
+use crate::banded::{bandwidth, BandedLdl};
+use crate::baseline::asymmetric_weights;
+use crate::block::{divide_rows, subtract_row, BLOCK_TILE_SIZE};
+use crate::boundary::periodic_ddmat;
+use crate::confidence_bands::normal_quantile;
+use crate::constraints::{beyond_bound, pinned, shape_weight};
+use crate::cross_validation::{select, FitStatistics};
+use crate::derivative::derivative;
 use crate::errors::WhittakerError;
-use crate::{CrossValidationResult, OptimisedSmoothResult, WHITTAKER_X_EPSILON};
-use nalgebra::{DMatrix, DVector};
-
+use crate::float::{cast, to_f64, WhittakerFloat};
+use crate::l_curve::{maximum_curvature_index, v_curve_index};
+use crate::missing::{missing_mask, missing_weights};
+use crate::optimise::brent_minimise;
+use crate::penalty::sum_penalties;
+use crate::prediction::merge_positions;
+use crate::robust::robust_weights;
+use crate::selected_inversion::inverse_diagonal;
+use crate::{
+    AdaptiveSmoothResult, BaselineMethod, BaselineResult, BlockLayout, Boundary,
+    BoundedSmoothResult, ConfidenceBandResult, CornerMethod, CrossValidationResult, LCurvePoint,
+    LCurveResult, LambdaSearch, MissingSmoothResult, OptimisedSmoothResult, PenaltyTerm,
+    PredictionResult, RobustLoss, RobustSmoothResult, SelectionCriterion, SerialCorrelation,
+    ShapeConstraint, ShapeSmoothResult, SmoothOptimalOptions, WHITTAKER_X_EPSILON,
+};
+
+use sprs::binop::csmat_binop;
 use sprs::FillInReduction::ReverseCuthillMcKee;
 use sprs::SymmetryCheck::CheckSymmetry;
-use sprs::{CsMat, CsMatView};
-use sprs_ldl::{Ldl, LdlNumeric};
+use sprs::{CsMat, CsMatView, PermOwned};
+use sprs_ldl::{Ldl, LdlNumeric, LdlSymbolic};
 
 #[cfg(feature = "rayon")]
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 /// Whitaker-Eilers Smoother and Interpolator
 ///
 /// The smoother must be created via [WhittakerSmoother::new()] and once created, can be reused to smooth multiple sets of data as
-/// efficiently as possible. You can update `lambda`, the smoothness; the order of the smoother `order`; the measurement `weights`; or the sample
-/// times/positions `x_input` through the provided functions. They enable you to control the smoother without remaking costly matrices.
+/// efficiently as possible. You can update `lambda`, the smoothness; the order of the smoother `order`; the measurement `weights`; the
+/// `penalty_weights`, which vary the smoothness along the data; or the sample times/positions `x_input` through the provided functions.
+/// They enable you to control the smoother without remaking costly matrices.
 ///
-pub struct WhittakerSmoother {
-    lambda: f64,
+#[derive(Clone)]
+pub struct WhittakerSmoother<T = f64> {
+    lambda: T,
     order: usize,
     data_length: usize,
-    x_input: Option<Vec<f64>>,
-    e_mat: CsMat<f64>,
-    d_mat: CsMat<f64>,
-    weights_mat: Option<CsMat<f64>>,
-    to_solve: CsMat<f64>,
-    ldl: LdlNumeric<f64, usize>,
+    x_input: Option<Vec<T>>,
+    e_mat: CsMat<T>,
+    d_mat: CsMat<T>,
+    weights_mat: Option<CsMat<T>>,
+    penalty_weights: Option<Vec<T>>,
+    additional_penalties: Vec<PenaltyTerm<T>>,
+    additional_penalty_mat: Option<CsMat<T>>,
+    boundary: Boundary<T>,
+    to_solve: CsMat<T>,
+    solver: Solver<T>,
+    missing_factorisation: Option<Box<MissingFactorisation<T>>>,
+}
+
+/// The factorisation of the system with missing measurements given zero weight.
+///
+/// Kept by `smooth_missing` so that series with the same missing measurements don't need refactorising. It is dropped whenever the
+/// smoother's own system changes.
+#[derive(Clone)]
+struct MissingFactorisation<T> {
+    missing: Vec<bool>,
+    weights: Vec<T>,
+    to_solve: CsMat<T>,
+    solver: Solver<T>,
 }
 
-impl WhittakerSmoother {
+/// The factorisation of the system to solve.
+///
+/// The system is banded so is normally factorised by the dedicated [BandedLdl]. The general sparse LDLᵀ is kept as a fallback for
+/// systems whose bands would take more storage than the sparse matrix itself, or which the banded factorisation cannot pivot.
+#[derive(Clone)]
+enum Solver<T> {
+    Banded(BandedLdl<T>),
+    Sparse {
+        ldl: Box<LdlNumeric<T, usize>>,
+        perm: PermOwned,
+    },
+}
+
+impl<T: WhittakerFloat> WhittakerSmoother<T> {
     /// Create a new Whittaker-Eilers smoother and interpolator.
     ///
     /// The smoother is configured through it's `lambda` and it's `order`. `Lambda` controls the smoothness of the data and `order` controls
@@ -47,13 +101,43 @@ impl WhittakerSmoother {
     /// * `x_input`: The time/position at which the y measurement was taken. Used to smooth unequally spaced data. Must be monotonically increasing.
     /// * `weights`: The weight of each y measurement.
     pub fn new(
-        lambda: f64,
+        lambda: T,
         order: usize,
         data_length: usize,
-        x_input: Option<&Vec<f64>>,
-        weights: Option<&Vec<f64>>,
-    ) -> Result<WhittakerSmoother, WhittakerError> {
-        let e_mat: CsMat<f64> = CsMat::eye(data_length);
+        x_input: Option<&Vec<T>>,
+        weights: Option<&Vec<T>>,
+    ) -> Result<WhittakerSmoother<T>, WhittakerError> {
+        Self::new_with_penalties(
+            &[PenaltyTerm::Difference { order, lambda }],
+            data_length,
+            x_input,
+            weights,
+        )
+    }
+
+    /// Create a new Whittaker-Eilers smoother and interpolator whose roughness penalty is a sum of terms.
+    ///
+    /// Combined penalties such as `λ₁D₁'D₁ + λ₂D₂'D₂` damp both the slope and the curvature of the smoothed data, and user-supplied
+    /// penalty matrices, such as a harmonic penalty for seasonal series, can be added to them. See [PenaltyTerm].
+    ///
+    /// The first term is the smoother's main term and must be a [PenaltyTerm::Difference]. It is the term whose `lambda` and `order`
+    /// are changed by `update_lambda` and `update_order`, tuned by `smooth_optimal`, and scaled by any penalty weights. The other terms
+    /// stay fixed until they are replaced with `update_penalties`.
+    ///
+    /// # Arguments:
+    /// * `penalties`: The terms of the roughness penalty. The first must be a difference term.
+    /// * `data_length`: The length of the data which is to be smoothed.
+    /// * `x_input`: The time/position at which the y measurement was taken. Used to smooth unequally spaced data. Must be monotonically increasing.
+    /// * `weights`: The weight of each y measurement.
+    pub fn new_with_penalties(
+        penalties: &[PenaltyTerm<T>],
+        data_length: usize,
+        x_input: Option<&Vec<T>>,
+        weights: Option<&Vec<T>>,
+    ) -> Result<WhittakerSmoother<T>, WhittakerError> {
+        let (lambda, order) = main_term(penalties)?;
+
+        let e_mat: CsMat<T> = CsMat::eye(data_length);
 
         if data_length < order {
             return Err(WhittakerError::DataTooShort(data_length, order));
@@ -61,55 +145,39 @@ impl WhittakerSmoother {
 
         let (d_mat, cloned_vals_x) = match x_input {
             Some(x_vec) => {
-                if data_length != x_vec.len() {
-                    return Err(WhittakerError::LengthMismatch(data_length, x_vec.len()));
-                }
-                let mut cloned_x_vals = Vec::with_capacity(data_length);
-                for i in 0..data_length - 1 {
-                    if x_vec[i] >= x_vec[i + 1] {
-                        return Err(WhittakerError::NotMonotonicallyIncreasing(i));
-                    }
-                    if (x_vec[i] - x_vec[i + 1]).abs() < WHITTAKER_X_EPSILON {
-                        return Err(WhittakerError::SampleRateError(i));
-                    }
-                    cloned_x_vals.push(x_vec[i]);
-                }
-                cloned_x_vals.push(x_vec[data_length - 1]);
+                validate_x_input(x_vec, data_length)?;
 
-                (ddmat(x_vec, x_vec.len(), order), Some(cloned_x_vals))
+                (ddmat(x_vec, x_vec.len(), order), Some(x_vec.to_vec()))
             }
             None => (diff_no_ddmat(&e_mat, order), None),
         };
 
-        let weights_mat: Option<CsMat<f64>> = match weights {
+        let weights_mat: Option<CsMat<T>> = match weights {
             Some(weights) => {
-                if data_length != weights.len() {
-                    return Err(WhittakerError::LengthMismatch(data_length, weights.len()));
-                }
+                validate_weights(weights, data_length)?;
 
-                let diags = (0..weights.len() + 1).collect::<Vec<usize>>();
-
-                Some(CsMat::new_csc(
-                    (weights.len(), weights.len()),
-                    diags[..].to_vec(),
-                    diags[..weights.len()].to_vec(),
-                    weights.to_vec(),
-                ))
+                Some(diagonal_matrix(weights))
             }
             None => None,
         };
 
-        let to_solve: CsMat<f64> = match weights_mat.as_ref() {
-            Some(weights) => weights + &(&(&d_mat.transpose_view() * &d_mat) * lambda),
-            None => &e_mat + &(&(&d_mat.transpose_view() * &d_mat) * lambda),
-        };
+        let additional_penalties = penalties[1..].to_vec();
+        let additional_penalty_mat = sum_penalties(
+            &additional_penalties,
+            data_length,
+            x_input.map(|x| &x[..]),
+            &Boundary::Open,
+        )?;
+
+        let to_solve = system_matrix(
+            weights_mat.as_ref().unwrap_or(&e_mat),
+            &d_mat,
+            None,
+            additional_penalty_mat.as_ref(),
+            lambda,
+        );
 
-        let ldl = Ldl::new()
-            .fill_in_reduction(sprs::FillInReduction::ReverseCuthillMcKee)
-            .check_symmetry(CheckSymmetry)
-            .check_perm(sprs::CheckPerm)
-            .numeric(to_solve.view())
-            .map_err(|x| WhittakerError::SolverError(x))?;
+        let solver = factorise(&to_solve)?;
 
         return Ok(WhittakerSmoother {
             lambda,
@@ -119,13 +187,18 @@ impl WhittakerSmoother {
             e_mat,
             d_mat,
             weights_mat,
+            penalty_weights: None,
+            additional_penalties,
+            additional_penalty_mat,
+            boundary: Boundary::Open,
             to_solve,
-            ldl,
+            solver,
+            missing_factorisation: None,
         });
     }
 
     /// Retrieve the smoother's current lambda.
-    pub fn get_lambda(&self) -> f64 {
+    pub fn get_lambda(&self) -> T {
         self.lambda
     }
 
@@ -139,30 +212,63 @@ impl WhittakerSmoother {
         self.data_length
     }
 
+    /// Retrieve the smoother's boundary conditions.
+    pub fn get_boundary(&self) -> Boundary<T> {
+        self.boundary
+    }
+
+    /// Retrieve the terms of the smoother's roughness penalty, starting with its main term.
+    pub fn get_penalties(&self) -> Vec<PenaltyTerm<T>> {
+        let mut penalties = vec![PenaltyTerm::Difference {
+            order: self.order,
+            lambda: self.lambda,
+        }];
+        penalties.extend(self.additional_penalties.iter().cloned());
+        penalties
+    }
+
     /// Updates the weights of the data to be smoothed.
     ///
     /// The length of weights should be equal to that of the data you are to smooth. The values of the weights should fall between 0 and 1.
+    /// They must be finite and not negative, and at least one must be positive.
     ///
     /// # Arguments:
     /// * `weights`: The weights of the measurements to be smoothed. The smaller the weight the more the measurement will be ignored. Setting a weight to 0 results in interpolation.
-    pub fn update_weights(&mut self, weights: &Vec<f64>) -> Result<(), WhittakerError> {
-        if self.data_length != weights.len() {
+    pub fn update_weights(&mut self, weights: &Vec<T>) -> Result<(), WhittakerError> {
+        validate_weights(weights, self.data_length)?;
+
+        self.data_length = weights.len();
+
+        self.weights_mat = Some(diagonal_matrix(weights));
+
+        self.update_lambda(self.lambda)?;
+        Ok(())
+    }
+
+    /// Updates the penalty weights, which vary the smoothness along the data.
+    ///
+    /// A single `lambda` oversmooths sharp features and undersmooths flat stretches. Penalty weights `V` scale the roughness penalty
+    /// point by point, so the system solved becomes `W + λD'VD`. Each difference takes the mean of the penalty weights of the points it
+    /// spans. Weights above 1 smooth a stretch more and weights below 1 smooth it less. They can be estimated automatically with
+    /// `smooth_adaptive`.
+    ///
+    /// # Arguments:
+    /// * `penalty_weights`: The penalty weight of each point. Must be as long as the data, positive, and finite.
+    pub fn update_penalty_weights(&mut self, penalty_weights: &[T]) -> Result<(), WhittakerError> {
+        if self.data_length != penalty_weights.len() {
             return Err(WhittakerError::LengthMismatch(
                 self.data_length,
-                weights.len(),
+                penalty_weights.len(),
             ));
         }
+        if penalty_weights
+            .iter()
+            .any(|v| !v.is_finite() || *v <= T::zero())
+        {
+            return Err(WhittakerError::InvalidParameter("penalty_weights"));
+        }
 
-        self.data_length = weights.len();
-
-        let diags = (0..weights.len() + 1).collect::<Vec<usize>>();
-
-        self.weights_mat = Some(CsMat::new_csc(
-            (weights.len(), weights.len()),
-            diags[..].to_vec(),
-            diags[..weights.len()].to_vec(),
-            weights.clone(),
-        ));
+        self.penalty_weights = Some(penalty_weights.to_vec());
 
         self.update_lambda(self.lambda)?;
         Ok(())
@@ -181,34 +287,119 @@ impl WhittakerSmoother {
 
         self.order = order;
 
-        self.d_mat = match &self.x_input {
-            Some(x) => ddmat(x, x.len(), order),
-            None => diff_no_ddmat(&self.e_mat, order),
-        };
+        self.d_mat = difference_matrix(
+            self.x_input.as_deref(),
+            self.data_length,
+            order,
+            &self.boundary,
+        );
 
         self.update_lambda(self.lambda)?;
         Ok(())
     }
 
+    /// Updates the boundary conditions of the Whittaker-Eilers smoother.
+    ///
+    /// With [Boundary::Periodic], the ends of the data are treated as neighbours. The difference matrices become circulant, wrapping
+    /// from the last sample back to the first, so the smoothed data has no break at the wrap point. For unequally spaced data, the
+    /// period gives the distance between the last sample and the first, wrapped, sample. All of the difference penalty terms, penalty
+    /// weights, cross validation, and `predict` follow the boundary conditions.
+    ///
+    /// Periodic differences no longer form a band, so the general sparse factorisation is used, making smoothing somewhat slower.
+    ///
+    /// # Arguments:
+    /// * `boundary`: The boundary conditions at the ends of the data.
+    pub fn update_boundary(&mut self, boundary: Boundary<T>) -> Result<(), WhittakerError> {
+        if let Boundary::Periodic { period } = boundary {
+            match (self.x_input.as_ref(), period) {
+                (Some(x), Some(period)) => {
+                    if !period.is_finite()
+                        || x[0] + period - x[self.data_length - 1] < cast(WHITTAKER_X_EPSILON)
+                    {
+                        return Err(WhittakerError::InvalidParameter("period"));
+                    }
+                }
+                (None, None) => {}
+                _ => return Err(WhittakerError::InvalidParameter("period")),
+            }
+        }
+
+        let additional_penalty_mat = sum_penalties(
+            &self.additional_penalties,
+            self.data_length,
+            self.x_input.as_deref(),
+            &boundary,
+        )?;
+
+        self.boundary = boundary;
+        self.additional_penalty_mat = additional_penalty_mat;
+        self.d_mat = difference_matrix(
+            self.x_input.as_deref(),
+            self.data_length,
+            self.order,
+            &self.boundary,
+        );
+
+        self.update_lambda(self.lambda)
+    }
+
+    /// Updates all of the terms of the roughness penalty.
+    ///
+    /// This generalises `update_lambda` and `update_order` to smoothers made with `new_with_penalties`. As there, the first term is the
+    /// main term and must be a [PenaltyTerm::Difference]. Only the system is refactorised; the smoother's weights and penalty weights
+    /// are kept.
+    ///
+    /// # Arguments:
+    /// * `penalties`: The terms of the roughness penalty. The first must be a difference term.
+    pub fn update_penalties(&mut self, penalties: &[PenaltyTerm<T>]) -> Result<(), WhittakerError> {
+        let (lambda, order) = main_term(penalties)?;
+        if self.data_length < order {
+            return Err(WhittakerError::DataTooShort(self.data_length, order));
+        }
+
+        let additional_penalties = penalties[1..].to_vec();
+        self.additional_penalty_mat = sum_penalties(
+            &additional_penalties,
+            self.data_length,
+            self.x_input.as_deref(),
+            &self.boundary,
+        )?;
+        self.additional_penalties = additional_penalties;
+
+        if order != self.order {
+            self.order = order;
+            self.d_mat = difference_matrix(
+                self.x_input.as_deref(),
+                self.data_length,
+                order,
+                &self.boundary,
+            );
+        }
+
+        self.update_lambda(lambda)
+    }
+
     /// Updates the smoothing constant `lambda` of the Whittaker-Eilers smoother.
     ///
     /// Efficiently update the target smoothness of the Whittaker smoother. The larger the `lambda`, the smoother the data.
     ///
     /// # Arguments:
     /// * `lambda`: The smoothing constant of the Whittaker-Eilers smoother.
-    pub fn update_lambda(&mut self, lambda: f64) -> Result<(), WhittakerError> {
+    pub fn update_lambda(&mut self, lambda: T) -> Result<(), WhittakerError> {
+        validate_lambda(lambda)?;
         self.lambda = lambda;
 
-        self.to_solve = match self.weights_mat.as_ref() {
-            Some(weights) => weights + &(&(&self.d_mat.transpose_view() * &self.d_mat) * lambda),
-            None => &self.e_mat + &(&(&self.d_mat.transpose_view() * &self.d_mat) * lambda),
-        };
+        let to_solve = system_matrix(
+            self.weights_mat.as_ref().unwrap_or(&self.e_mat),
+            &self.d_mat,
+            self.difference_penalty_weights().as_deref(),
+            self.additional_penalty_mat.as_ref(),
+            lambda,
+        );
 
-        self.ldl = Ldl::new()
-            .fill_in_reduction(ReverseCuthillMcKee)
-            .check_symmetry(CheckSymmetry)
-            .numeric(self.to_solve.view())
-            .map_err(|x| WhittakerError::SolverError(x))?;
+        self.solver.refactorise(&self.to_solve, &to_solve)?;
+        self.to_solve = to_solve;
+        self.missing_factorisation = None;
 
         Ok(())
     }
@@ -220,32 +411,114 @@ impl WhittakerSmoother {
     /// will result in a lot of overhead.
     ///
     /// # Arguments
-    /// * `y_input`: The values which are to be smoothed and interpolated by the Whittaker-Eilers smoother.
+    /// * `y_input`: The values which are to be smoothed and interpolated by the Whittaker-Eilers smoother. Must be finite; use `smooth_missing` for data with missing values.
     ///
     /// # Returns:
     /// The smoothed and interpolated data.
-    pub fn smooth(&self, y_input: &[f64]) -> Result<Vec<f64>, WhittakerError> {
+    pub fn smooth(&self, y_input: &[T]) -> Result<Vec<T>, WhittakerError> {
         if y_input.len() != self.data_length {
             return Err(WhittakerError::LengthMismatch(
                 self.data_length,
                 y_input.len(),
             ));
         }
-        return if self.weights_mat.is_some() {
-            Ok(self.ldl.solve(
-                self.weights_mat
-                    .as_ref()
-                    .unwrap()
+        validate_finite(y_input, "y_input")?;
+
+        match self.weights_mat.as_ref() {
+            Some(weights_mat) => Ok(self.solver.solve(
+                &weights_mat
                     .diag()
                     .data()
                     .iter()
                     .zip(y_input)
-                    .map(|(a, b)| a * b)
-                    .collect::<Vec<f64>>(),
-            ))
-        } else {
-            Ok(self.ldl.solve(y_input))
+                    .map(|(a, b)| *a * *b)
+                    .collect::<Vec<T>>(),
+            )),
+            None => Ok(self.solver.solve(y_input)),
+        }
+    }
+
+    /// Run Whittaker-Eilers smoothing on data with missing values.
+    ///
+    /// Missing samples, given as NaN or any other non-finite value, would otherwise spread through the solve and poison every smoothed
+    /// point. Here they are given zero weight, on top of any weights the smoother already has, so that they are interpolated.
+    ///
+    /// The system with the missing samples removed has to be refactorised, but its factorisation is kept. Series with the same
+    /// missing samples reuse it until a different pattern of missing samples is smoothed or the smoother is updated. When no samples
+    /// are missing this is just `smooth`.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed, with non-finite values where samples are missing.
+    ///
+    /// # Returns:
+    /// [MissingSmoothResult]: The smoothed data, with the missing samples interpolated, and which samples were imputed.
+    pub fn smooth_missing(
+        &mut self,
+        y_input: &[T],
+    ) -> Result<MissingSmoothResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+
+        let imputed = missing_mask(y_input);
+        if !imputed.contains(&true) {
+            return Ok(MissingSmoothResult {
+                smoothed: self.smooth(y_input)?,
+                imputed,
+            });
+        }
+
+        let factorisation = match self.missing_factorisation.take() {
+            Some(factorisation) if factorisation.missing == imputed => factorisation,
+            previous => {
+                let weights = missing_weights(self.get_weights(), &imputed);
+                if weights.iter().all(|weight| weight.is_zero()) {
+                    // The cached factorisation is still valid for its own missing samples, so it is kept.
+                    self.missing_factorisation = previous;
+                    return Err(WhittakerError::AllWeightsZero);
+                }
+                let to_solve = system_matrix(
+                    &diagonal_matrix(&weights),
+                    &self.d_mat,
+                    self.difference_penalty_weights().as_deref(),
+                    self.additional_penalty_mat.as_ref(),
+                    self.lambda,
+                );
+
+                // Only the weights have changed from a previous pattern of missing samples, so its sparsity pattern and any
+                // symbolic analysis can be reused.
+                let solver = match previous {
+                    Some(previous) => {
+                        let mut solver = previous.solver;
+                        solver.refactorise(&previous.to_solve, &to_solve)?;
+                        solver
+                    }
+                    None => factorise(&to_solve)?,
+                };
+
+                Box::new(MissingFactorisation {
+                    missing: imputed.clone(),
+                    weights,
+                    to_solve,
+                    solver,
+                })
+            }
         };
+
+        let weighted_y = y_input
+            .iter()
+            .zip(&factorisation.weights)
+            .zip(&imputed)
+            .map(|((y, w), missing)| if *missing { T::zero() } else { *y * *w })
+            .collect::<Vec<T>>();
+
+        let smoothed = factorisation.solver.solve(&weighted_y);
+        self.missing_factorisation = Some(factorisation);
+
+        Ok(MissingSmoothResult { smoothed, imputed })
     }
 
     /// Run parallel Whittaker-Eilers smoothing and interpolation for multiple data series
@@ -260,10 +533,10 @@ impl WhittakerSmoother {
     /// # Returns:
     /// The smoothed and interpolated data as a vector of results.
     #[cfg(feature = "rayon")]
-    pub fn smooth_parallel<'a, T>(&self, y_inputs: &'a [T]) -> Vec<Result<Vec<f64>, WhittakerError>>
+    pub fn smooth_parallel<'a, Y>(&self, y_inputs: &'a [Y]) -> Vec<Result<Vec<T>, WhittakerError>>
     where
-        [T]: IntoParallelRefIterator<'a>,
-        <[T] as IntoParallelRefIterator<'a>>::Item: AsRef<[f64]>,
+        [Y]: IntoParallelRefIterator<'a>,
+        <[Y] as IntoParallelRefIterator<'a>>::Item: AsRef<[T]>,
     {
         y_inputs
             .par_iter()
@@ -271,254 +544,940 @@ impl WhittakerSmoother {
             .collect()
     }
 
-    /// Run Whittaker-Eilers smoothing, interpolation and cross validation.
+    /// Run Whittaker-Eilers smoothing and interpolation for a block of data series stored in one slice.
     ///
-    /// This function will run the smoother and assess the cross validation error on the result. This is defined in Eilers'
-    /// 2003 paper: "A Perfect Smoother".  It involves computing the "hat matrix" or "smoother matrix" which inverses a sparse matrix. The
-    /// inverse of a sparse matrix is usually dense, so this function will take much longer to run in comparison to just running `smooth`.
+    /// Smoothing many series with `smooth` or `smooth_parallel` solves through the factorised system once per series, allocating
+    /// each result. Here the series are solved together, a tile of them at a time, with the samples of all the series in a tile at
+    /// each position next to each other in memory. Each tile takes a single pass through the factorisation, and the results are
+    /// written straight into `output`. All series share the smoother's weights.
     ///
     /// # Arguments
-    /// * `y_input`: The values which are to be smoothed and interpolated and have their cross validation error calculated.
-    ///
-    /// # Returns:
-    /// [CrossValidationResult]: The smoothed data, lambda it was smoothed at, and the cross validation error. Technically square-rooted cross validation error.
-    pub fn smooth_and_cross_validate(
+    /// * `y_block`: The `n × m` block holding `m` series of length `n`, one per column.
+    /// * `series`: The number of series, `m`.
+    /// * `layout`: How the block is laid out in `y_block`. The same layout is used for `output`.
+    /// * `output`: Where the smoothed and interpolated data is written. Elements of `output` outside the block are left untouched.
+    ///   If a non-finite value is found in `y_block`, the series before it may already have been written.
+    pub fn smooth_block(
         &self,
-        y_input: &[f64],
-    ) -> Result<CrossValidationResult, WhittakerError> {
-        if y_input.len() != self.data_length {
+        y_block: &[T],
+        series: usize,
+        layout: BlockLayout,
+        output: &mut [T],
+    ) -> Result<(), WhittakerError> {
+        let required_length = layout.required_length(self.data_length, series)?;
+        if y_block.len() < required_length {
             return Err(WhittakerError::LengthMismatch(
-                self.data_length,
-                y_input.len(),
+                required_length,
+                y_block.len(),
+            ));
+        }
+        if output.len() < required_length {
+            return Err(WhittakerError::LengthMismatch(
+                required_length,
+                output.len(),
             ));
         }
+        if series == 0 {
+            return Ok(());
+        }
 
-        let smoothed_series = self.smooth(y_input)?;
-        let smoothed_dvec = DVector::from_vec(smoothed_series.clone());
-        let y_input_dvec = DVector::from_vec(y_input.to_vec());
-        let identity_dvec = DVector::from_element(self.data_length, 1.0);
+        // The rows of the factorised system, after any fill-in reducing permutation, and their weights.
+        let rows = (0..self.data_length)
+            .map(|i| self.solver.original_row(i))
+            .collect::<Vec<usize>>();
+        let row_weights = match self.get_weights() {
+            Some(weights) => rows.iter().map(|row| weights[*row]).collect(),
+            None => vec![T::one(); self.data_length],
+        };
+
+        // The series are solved a tile at a time, so that the tile stays in cache through both substitutions.
+        let tile_columns = (BLOCK_TILE_SIZE / self.data_length).clamp(1, series);
+        let mut tile = vec![T::zero(); self.data_length * tile_columns];
+
+        let (sample_step, series_step) = layout.steps();
+
+        for first in (0..series).step_by(tile_columns) {
+            let columns = tile_columns.min(series - first);
+            let tile = &mut tile[..self.data_length * columns];
+
+            for ((row, source), weight) in
+                tile.chunks_exact_mut(columns).zip(&rows).zip(&row_weights)
+            {
+                let start = source * sample_step + first * series_step;
+                let mut finite = true;
+                for (value, y) in row
+                    .iter_mut()
+                    .zip(y_block[start..].iter().step_by(series_step))
+                {
+                    finite &= y.is_finite();
+                    *value = *weight * *y;
+                }
 
-        if self.data_length > 100 {
-            let n = 100;
-            let e1: CsMat<f64> = CsMat::eye(n);
+                if !finite {
+                    let offset = (0..columns)
+                        .position(|j| !y_block[start + j * series_step].is_finite())
+                        .unwrap_or(0);
+                    return Err(WhittakerError::InvalidValue(
+                        "y_block",
+                        start + offset * series_step,
+                    ));
+                }
+            }
 
-            let g = (0..n)
-                .map(|x| {
-                    ((x as f64) * ((self.data_length - 1) as f64 / (n - 1) as f64)).round() as usize
-                })
-                // .collect::<HashSet<usize>>()
-                // .into_iter()
-                .collect::<Vec<usize>>();
-
-            let d1 = match &self.x_input {
-                Some(x) => ddmat(
-                    &g.iter().map(|index| x[*index]).collect::<Vec<f64>>(),
-                    g.len(),
-                    self.order,
-                ),
-                None => diff_no_ddmat(&e1, self.order),
-            };
-            let lambda1 =
-                self.lambda * (n as f64 / self.data_length as f64).powf(2.0 * self.order as f64);
+            self.solver.solve_block(tile, columns);
 
-            let to_inverse = match self.weights_mat.as_ref() {
-                Some(x) => {
-                    let weights_vec = g
-                        .iter()
-                        .map(|index| x.diag().data()[*index])
-                        .collect::<Vec<f64>>();
+            for (row, target) in tile.chunks_exact(columns).zip(&rows) {
+                let start = target * sample_step + first * series_step;
+                for (value, output) in row
+                    .iter()
+                    .zip(output[start..].iter_mut().step_by(series_step))
+                {
+                    *output = *value;
+                }
+            }
+        }
+
+        Ok(())
+    }
 
-                    let diags = (0..weights_vec.len() + 1).collect::<Vec<usize>>();
+    /// Run Whittaker-Eilers smoothing and interpolation for multiple data series, each with its own weights.
+    ///
+    /// Useful when every series is sampled at the same positions but has its own missing or unreliable measurements, such as
+    /// satellite pixels with their own cloud masks. The series share the smoother's `x_input`, order, lambda and penalties, so the
+    /// penalty matrix is built once. With the sparse factorisation, used for periodic boundaries and wide penalties, the fill-in
+    /// reducing permutation and symbolic analysis are also computed once and only the numeric factorisation is redone for each
+    /// series. The banded factorisation has no symbolic step and is simply redone for each series. The smoother's own weights are
+    /// ignored. With the `rayon` feature the series are smoothed in parallel.
+    ///
+    /// # Arguments
+    /// * `y_inputs`: A vec or slice of vecs or slices which are to be smoothed and interpolated by the Whittaker-Eilers smoother.
+    /// * `weights`: The weights of the measurements of each series, in the same order as `y_inputs`.
+    ///
+    /// # Returns:
+    /// The smoothed and interpolated data as a vector of results, one per series. Errors if the number of weight vectors doesn't match
+    /// the number of series.
+    pub fn smooth_batch<Y, W>(
+        &self,
+        y_inputs: &[Y],
+        weights: &[W],
+    ) -> Result<Vec<Result<Vec<T>, WhittakerError>>, WhittakerError>
+    where
+        Y: AsRef<[T]> + Sync,
+        W: AsRef<[T]> + Sync,
+    {
+        if y_inputs.len() != weights.len() {
+            return Err(WhittakerError::LengthMismatch(
+                y_inputs.len(),
+                weights.len(),
+            ));
+        }
 
-                    let weights_mat = CsMat::new_csc(
-                        (weights_vec.len(), weights_vec.len()),
-                        diags[..].to_vec(),
-                        diags[..weights_vec.len()].to_vec(),
-                        weights_vec.clone(),
-                    );
+        let penalty = penalty_matrix(
+            &self.d_mat,
+            self.difference_penalty_weights().as_deref(),
+            self.additional_penalty_mat.as_ref(),
+            self.lambda,
+        );
+
+        // The weights only change the diagonal, so every series' system has the sparsity pattern of the smoother's own. When the
+        // sparse factorisation is in use, its fill-in reducing permutation and symbolic analysis are therefore shared by all series.
+        let symbolic = match &self.solver {
+            Solver::Sparse { perm, .. } => Some((
+                LdlSymbolic::new_perm(self.to_solve.view(), perm.clone(), CheckSymmetry),
+                perm,
+            )),
+            Solver::Banded(_) => None,
+        };
 
-                    &weights_mat + &(&(&d1.transpose_view() * &d1) * lambda1)
+        let smooth_series = |y_input: &Y, weights: &W| -> Result<Vec<T>, WhittakerError> {
+            let (y_input, weights) = (y_input.as_ref(), weights.as_ref());
+            if y_input.len() != self.data_length {
+                return Err(WhittakerError::LengthMismatch(
+                    self.data_length,
+                    y_input.len(),
+                ));
+            }
+            validate_weights(weights, self.data_length)?;
+            validate_finite(y_input, "y_input")?;
+
+            let to_solve = csmat_binop(
+                diagonal_matrix(weights).to_csr().view(),
+                penalty.view(),
+                |a, b| *a + *b,
+            );
+
+            let solver = match &symbolic {
+                Some((symbolic, perm)) if same_pattern(&self.to_solve, &to_solve) => {
+                    factorise_numeric(&to_solve, symbolic.clone(), perm)?
                 }
-                None => &e1 + &(&(&d1.transpose_view() * &d1) * lambda1),
+                _ => factorise(&to_solve)?,
             };
 
-            let hat_matrix = DMatrix::from_iterator(
-                to_inverse.rows(),
-                to_inverse.cols(),
-                to_inverse.to_dense().into_iter(),
-            )
-            .lu()
-            .solve(&DMatrix::identity(n, n))
-            .ok_or_else(|| WhittakerError::MatrixNotInvertible)?;
+            Ok(solver.solve(
+                &y_input
+                    .iter()
+                    .zip(weights)
+                    .map(|(y, w)| *y * *w)
+                    .collect::<Vec<T>>(),
+            ))
+        };
 
-            let h1 = hat_matrix.diagonal();
+        #[cfg(feature = "rayon")]
+        let smoothed = y_inputs
+            .par_iter()
+            .zip(weights)
+            .map(|(y_input, weights)| smooth_series(y_input, weights))
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        let smoothed = y_inputs
+            .iter()
+            .zip(weights)
+            .map(|(y_input, weights)| smooth_series(y_input, weights))
+            .collect();
+
+        Ok(smoothed)
+    }
 
-            let mut u = DVector::from_element(self.data_length, 0.0);
+    /// Run Whittaker-Eilers smoothing, interpolation and cross validation.
+    ///
+    /// This function will run the smoother and assess the cross validation error on the result. This is defined in Eilers'
+    /// 2003 paper: "A Perfect Smoother".  It involves computing the diagonal of the "hat matrix" or "smoother matrix". Only the
+    /// elements of the inverse within the band of the factorised system are computed, so this remains linear in the length of the data, though slower than just running `smooth`.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed and interpolated and have their cross validation error calculated.
+    ///
+    /// # Returns:
+    /// [CrossValidationResult]: The smoothed data, lambda it was smoothed at, and the cross validation error. Technically square-rooted cross validation error.
+    pub fn smooth_and_cross_validate(
+        &self,
+        y_input: &[T],
+    ) -> Result<CrossValidationResult<T>, WhittakerError> {
+        self.smooth_and_score(y_input, SelectionCriterion::CrossValidation)
+    }
 
-            let k = (self.data_length as f64 / 2.0).floor() as usize;
+    /// Run Whittaker-Eilers smoothing and score the result with a chosen criterion.
+    ///
+    /// Like `smooth_and_cross_validate`, this computes the diagonal of the hat matrix. Its trace, the effective degrees of freedom, is
+    /// reported alongside the score. The leave-one-out cross validation error is always computed too.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed, interpolated, and scored.
+    /// * `criterion`: The criterion used to score the smoothed data. See [SelectionCriterion].
+    ///
+    /// # Returns:
+    /// [CrossValidationResult]: The smoothed data, lambda it was smoothed at, the cross validation error, the score, and the effective degrees of freedom.
+    pub fn smooth_and_score(
+        &self,
+        y_input: &[T],
+        criterion: SelectionCriterion,
+    ) -> Result<CrossValidationResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+        if let SelectionCriterion::MallowsCp(variance) = criterion {
+            if variance.is_nan() || variance <= 0.0 {
+                return Err(WhittakerError::InvalidParameter("noise_variance"));
+            }
+        }
+        let held_out_error = match criterion {
+            SelectionCriterion::KFold(folds) => {
+                if folds < 2 || folds > self.data_length {
+                    return Err(WhittakerError::InvalidParameter("folds"));
+                }
+                Some(self.held_out_error(y_input, folds, |i| i % folds)?)
+            }
+            SelectionCriterion::BlockedCrossValidation(blocks) => {
+                if blocks < 2 || blocks > self.data_length {
+                    return Err(WhittakerError::InvalidParameter("blocks"));
+                }
+                let data_length = self.data_length;
+                Some(self.held_out_error(y_input, blocks, |i| i * blocks / data_length)?)
+            }
+            _ => None,
+        };
 
-            let k1 = (n as f64 / 2.0).floor() as usize;
+        let smoothed_series = self.smooth(y_input)?;
+        let hat_diagonal = self.hat_matrix_diagonal();
 
-            u[k - 1] = 1.0;
+        // TODO! Investigate using I - trace(hat_matrix)/ N. Can lead to less undersmoothing. Way to avoid solver/inverse?
+        let r = y_input
+            .iter()
+            .zip(&smoothed_series)
+            .zip(&hat_diagonal)
+            .map(|((y, z), h)| (*y - *z) / (T::one() - *h));
 
-            let v = self.ldl.solve(u.data.as_slice()); // Doesn't need weights
+        let cve = match self.weights_mat.as_ref() {
+            Some(weights) => {
+                let weights = weights.diag();
+                r.zip(weights.data()).map(|(r, w)| r * r * *w).sum::<T>()
+                    / weights.data().iter().sum::<T>()
+            }
+            None => r.map(|r| r * r).sum::<T>() / cast(self.data_length as f64),
+        }
+        .sqrt();
+
+        let stats = self.fit_statistics(y_input, &smoothed_series, &hat_diagonal);
+        let score = criterion.score(to_f64(held_out_error.unwrap_or(cve)), &stats);
+
+        Ok(CrossValidationResult {
+            lambda: self.get_lambda(),
+            smoothed: smoothed_series,
+            cross_validation_error: cve,
+            criterion,
+            score: cast(score),
+            effective_degrees_of_freedom: cast(stats.effective_degrees_of_freedom),
+        })
+    }
 
-            let f = (0..self.data_length)
-                .map(|x| {
-                    ((x as f64) * ((n - 1) as f64 / (self.data_length - 1) as f64)).round() as usize
-                })
-                .collect::<Vec<usize>>();
+    /// Run Whittaker-Eilers smoothing and compute pointwise confidence bands.
+    ///
+    /// The bands are Bayesian confidence intervals (Wahba, 1983). The residual variance is estimated from the weighted residuals and the
+    /// effective degrees of freedom of the fit, the trace of the hat matrix. The standard error of each smoothed point then comes from the
+    /// diagonal of `(W + λD'D)^-1`, which is the hat matrix with the weights divided out. Interpolated points therefore have wider bands.
+    /// Like `smooth_and_cross_validate`, this requires the hat matrix so is slower than `smooth`.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed and interpolated.
+    /// * `confidence_level`: The confidence level of the bands, between 0 and 1. For example 0.95.
+    ///
+    /// # Returns:
+    /// [ConfidenceBandResult]: The smoothed data, the standard error of each point, and the lower and upper bands.
+    pub fn smooth_with_confidence_bands(
+        &self,
+        y_input: &[T],
+        confidence_level: T,
+    ) -> Result<ConfidenceBandResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+        if !(confidence_level > T::zero() && confidence_level < T::one()) {
+            return Err(WhittakerError::InvalidParameter("confidence_level"));
+        }
 
-            let vk = v[k - 1];
-            let h1k1 = h1[k1 - 1];
+        let smoothed = self.smooth(y_input)?;
+        let inverse_diagonal = self.solver.inverse_diagonal();
+        let hat_diagonal = match self.weights_mat.as_ref() {
+            Some(weights) => inverse_diagonal
+                .iter()
+                .zip(weights.diag().data())
+                .map(|(z, w)| *z * *w)
+                .collect(),
+            None => inverse_diagonal.clone(),
+        };
 
-            let h = match self.weights_mat.as_ref() {
-                Some(x) => f
-                    .iter()
-                    .zip(x.diag().data())
-                    .map(|(index, weight)| weight * h1[*index] * vk / h1k1)
-                    .collect::<Vec<f64>>(),
-                None => f
-                    .iter()
-                    .map(|index| h1[*index] * vk / h1k1)
-                    .collect::<Vec<f64>>(),
-            };
+        let stats = self.fit_statistics(y_input, &smoothed, &hat_diagonal);
 
-            let h = DVector::from_vec(h);
+        if stats.observations - stats.effective_degrees_of_freedom <= f64::EPSILON {
+            return Err(WhittakerError::InvalidParameter("lambda"));
+        }
 
-            let r = (y_input_dvec - smoothed_dvec).component_div(&(identity_dvec - h));
-            let weights_vec = self
-                .weights_mat
-                .as_ref()
-                .map(|x| DVector::from_row_slice(x.diag().data()));
-            let cve = match weights_vec.as_ref() {
-                Some(weights) => (r.transpose() * r.component_mul(weights)).sum() / weights.sum(),
-                None => (r.transpose() * r).sum() / self.data_length as f64,
-            }
-            .sqrt();
+        let effective_degrees_of_freedom = cast(stats.effective_degrees_of_freedom);
+        let residual_variance: T = cast(
+            stats.residual_sum_of_squares
+                / (stats.observations - stats.effective_degrees_of_freedom),
+        );
+
+        let standard_errors = inverse_diagonal
+            .iter()
+            .map(|z| (residual_variance * *z).sqrt())
+            .collect::<Vec<T>>();
+
+        let quantile: T = cast(normal_quantile(0.5 + to_f64(confidence_level) / 2.0));
+
+        let lower = smoothed
+            .iter()
+            .zip(&standard_errors)
+            .map(|(z, se)| *z - quantile * *se)
+            .collect();
+        let upper = smoothed
+            .iter()
+            .zip(&standard_errors)
+            .map(|(z, se)| *z + quantile * *se)
+            .collect();
+
+        Ok(ConfidenceBandResult {
+            smoothed,
+            standard_errors,
+            lower,
+            upper,
+            residual_variance,
+            effective_degrees_of_freedom,
+        })
+    }
 
-            Ok(CrossValidationResult {
-                lambda: self.get_lambda(),
-                smoothed: smoothed_series,
-                cross_validation_error: cve,
-            })
-        } else {
-            let mut hat_matrix = DMatrix::from_iterator(
-                self.to_solve.rows(),
-                self.to_solve.cols(),
-                self.to_solve.to_dense().into_iter(),
-            )
-            .lu()
-            .solve(&DMatrix::identity(self.data_length, self.data_length))
-            .ok_or_else(|| WhittakerError::MatrixNotInvertible)?;
-
-            let weights_vec = self
-                .weights_mat
-                .as_ref()
-                .map(|x| DVector::from_row_slice(x.diag().data()));
-
-            if weights_vec.is_some() {
-                hat_matrix *= DMatrix::from_diagonal(weights_vec.as_ref().unwrap());
-            }
-            let r = (y_input_dvec - smoothed_dvec)
-                .component_div(&(identity_dvec - hat_matrix.diagonal())); // TODO! Investigate using I - trace(hat_matrix)/ N. Can lead to less undersmoothing. Way to avoid solver/inverse?
-
-            let cve = match weights_vec.as_ref() {
-                Some(weights) => (r.transpose() * r.component_mul(weights)).sum() / weights.sum(),
-                None => (r.transpose() * r).sum() / self.data_length as f64,
-            }
-            .sqrt();
-
-            Ok(CrossValidationResult {
-                lambda: self.get_lambda(),
-                smoothed: smoothed_series,
-                cross_validation_error: cve,
-            })
+    /// Run Whittaker-Eilers smoothing and estimate a derivative of the smoothed data.
+    ///
+    /// Velocities and accelerations can be estimated from smoothed positions. For unequally spaced data, the divided differences that
+    /// the smoother penalises are used, giving the derivative with respect to `x_input`. For equally spaced data, the plain differences
+    /// are used, giving the derivative with respect to the sample index. Divide by the sample spacing raised to `derivative_order` to
+    /// convert these to physical units.
+    ///
+    /// Each difference estimates the derivative at the centre of the samples it spans, so the differences are linearly interpolated
+    /// back to the sample positions. At the boundaries they are linearly extrapolated from the two nearest differences. The smoothed
+    /// data is a polynomial of degree `order - 1` where it is interpolated or extrapolated, so derivatives of order `order` and above
+    /// are close to zero there.
+    ///
+    /// With [Boundary::Periodic], the circulant differences that wrap from the last sample back to the first are used, so the
+    /// derivative is interpolated across the wrap point rather than extrapolated at the ends.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed.
+    /// * `derivative_order`: The order of the derivative. 1 gives the velocity of position data, 2 the acceleration. Must be less than the data length.
+    ///
+    /// # Returns:
+    /// The derivative of the smoothed data at each sample position.
+    pub fn smooth_derivative(
+        &self,
+        y_input: &[T],
+        derivative_order: usize,
+    ) -> Result<Vec<T>, WhittakerError> {
+        if self.data_length <= derivative_order {
+            return Err(WhittakerError::DataTooShort(
+                self.data_length,
+                derivative_order,
+            ));
         }
+        let smoothed = self.smooth(y_input)?;
+
+        Ok(derivative(
+            &smoothed,
+            self.x_input.as_deref(),
+            derivative_order,
+            &self.boundary,
+        ))
     }
 
-    /// Runs Whittaker-Eilers smoothing for a variety of lambdas and selects the optimally smoothed time series.
+    /// Run Whittaker-Eilers smoothing and evaluate the smoothed curve at new positions.
     ///
-    /// This function runs the smoother for lambdas varying from 1e-2 to 1e8 in logarithmic steps of 0.5. It computes the
-    /// hat/smoother matrix and finds the optimal lambda for the data. If the time-series exhibits serial correlation the optimal
-    /// lambda can be very small and mean the smoothed data doesn't differ from the input data. To avoid this, use `break_serial_correlation = true`
+    /// The query positions are merged into the sample positions as measurements with zero weight, so that they are interpolated, and
+    /// the merged series is smoothed. This is useful for resampling irregularly sampled data onto a regular grid. Queries outside of
+    /// the sample positions are extrapolated as a polynomial of degree `order - 1`, or with [Boundary::Periodic] are wrapped into the
+    /// period. A query closer than [WHITTAKER_X_EPSILON] to a sample is given the smoothed value at that sample.
     ///
-    /// It will return the smoothed data, lambda, and cross validation error for each lambda tested!
+    /// If the smoother was made without an `x_input`, the samples are taken to lie at `0, 1, 2, ...`. Lambda is scaled so that the
+    /// smoothing matches that of `smooth`.
     ///
-    /// As the smoother matrix requires the inversion of a sparse matrix (which usually becomes a dense matrix), this code is extremely slow compared to smoothing
-    /// with a known lambda. Use sparingly!
+    /// As a new smoother has to be built for the merged positions, this is as costly as creating a [WhittakerSmoother]. Smoothers with a
+    /// user-supplied [PenaltyTerm::Matrix] cannot be extended to new positions, so return an error.
     ///
     /// # Arguments
-    /// * `y_input`: The values which are to be smoothed, interpolated, and cross validated for a variety of lambdas.
-    /// * `break_serial_correlation`: Default here should be `true`. Without it data that exhibits serial correlation is barely smoothed.
+    /// * `y_input`: The values which are to be smoothed.
+    /// * `x_query`: The positions at which to evaluate the smoothed curve. They can be in any order.
     ///
     /// # Returns:
-    /// [OptimisedSmoothResult]: The smoothed data, lambda, and error for each tested lambda. Calling get_optimal, returns the best smoothed series.
-    pub fn smooth_optimal(
-        &mut self,
-        y_input: &[f64],
-        break_serial_correlation: bool,
-    ) -> Result<OptimisedSmoothResult, WhittakerError> {
-        let step = 0.5;
-        let mut start_lambda_log = (1e-5_f64).log10();
-        let end_lambda_log = (1e8_f64).log10();
+    /// [PredictionResult]: The smoothed curve at the query positions and the smoothed data at the sample positions.
+    pub fn predict(
+        &self,
+        y_input: &[T],
+        x_query: &[T],
+    ) -> Result<PredictionResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+        if x_query.iter().any(|x| !x.is_finite()) {
+            return Err(WhittakerError::InvalidParameter("x_query"));
+        }
 
-        let mut optimal_index = 0;
-        let mut validation_results = Vec::new();
-        let mut min_cve = f64::MAX;
-
-        let mut possible_new_config = if break_serial_correlation {
-            let every_n_y_input = every_fifth_element(y_input);
-
-            let new_length = every_n_y_input.len();
-
-            let every_n_x_input = self.x_input.as_ref().map(|x| every_fifth_element(&x));
-
-            let every_n_weight = self
-                .weights_mat
-                .as_ref()
-                .map(|x| every_fifth_element(x.diag().data()));
-
-            let new_smoother = WhittakerSmoother::new(
-                1.0,
-                self.order,
-                new_length,
-                every_n_x_input.as_ref(),
-                every_n_weight.as_ref(),
-            )?;
-            Some((new_smoother, every_n_y_input))
-        } else {
-            None
+        let x_input = match self.x_input.as_ref() {
+            Some(x_input) => x_input.clone(),
+            None => (0..self.data_length).map(|i| cast(i as f64)).collect(),
         };
-
-        let mut loop_counter = 0;
-        while (start_lambda_log - end_lambda_log - step).abs() > 1e-6 {
-            let new_lambda = 10_f64.powf(start_lambda_log);
-
-            let res = match possible_new_config.as_mut() {
-                Some((new_smoother, y)) => {
-                    new_smoother.update_lambda(new_lambda)?;
-                    new_smoother.smooth_and_cross_validate(y)?
-                }
-                None => {
-                    self.update_lambda(new_lambda)?;
-                    self.smooth_and_cross_validate(y_input)?
+        let boundary = match self.boundary {
+            Boundary::Open => Boundary::Open,
+            Boundary::Periodic { period } => Boundary::Periodic {
+                period: Some(period.unwrap_or_else(|| cast(self.data_length as f64))),
+            },
+        };
+        let penalties = self
+            .get_penalties()
+            .into_iter()
+            .map(|term| match term {
+                PenaltyTerm::Difference { order, lambda } if self.x_input.is_none() => {
+                    // Divided differences of unit spaced samples are the plain differences divided by order!.
+                    let factorial = (1..=order).map(|k| k as f64).product::<f64>();
+                    Ok(PenaltyTerm::Difference {
+                        order,
+                        lambda: lambda * cast(factorial * factorial),
+                    })
                 }
-            };
-
-            if res.cross_validation_error < min_cve {
-                optimal_index = loop_counter;
-                min_cve = res.cross_validation_error
+                PenaltyTerm::Difference { .. } => Ok(term),
+                // A user-supplied penalty matrix cannot be extended to the query positions.
+                PenaltyTerm::Matrix { .. } => Err(WhittakerError::InvalidParameter("penalties")),
+            })
+            .collect::<Result<Vec<PenaltyTerm<T>>, WhittakerError>>()?;
+
+        let merged = match boundary {
+            Boundary::Periodic {
+                period: Some(period),
+            } => {
+                // Queries are wrapped into the period starting at the first sample.
+                let start = x_input[0];
+                let wrapped = x_query
+                    .iter()
+                    .map(|x| {
+                        let offset = (*x - start) % period;
+                        let offset = if offset < T::zero() {
+                            offset + period
+                        } else {
+                            offset
+                        };
+                        if period - offset < cast(WHITTAKER_X_EPSILON) {
+                            start
+                        } else {
+                            start + offset
+                        }
+                    })
+                    .collect::<Vec<T>>();
+                merge_positions(&x_input, &wrapped)
             }
-            validation_results.push(res);
+            _ => merge_positions(&x_input, x_query),
+        };
 
-            start_lambda_log += step;
-            loop_counter += 1;
+        let sample_weights = self.get_weights();
+        let mut samples: usize = 0;
+        let mut weights = Vec::with_capacity(merged.positions.len());
+        let mut merged_y = Vec::with_capacity(merged.positions.len());
+        let mut penalty_weights = Vec::with_capacity(merged.positions.len());
+        for is_sample in &merged.is_sample {
+            if let Some(v) = self.penalty_weights.as_ref() {
+                // Queries take the penalty weight of the sample before them, or of the first sample.
+                let nearest = if *is_sample {
+                    samples
+                } else {
+                    samples.saturating_sub(1)
+                };
+                penalty_weights.push(v[nearest]);
+            }
+            if *is_sample {
+                weights.push(sample_weights.as_ref().map_or(T::one(), |w| w[samples]));
+                merged_y.push(y_input[samples]);
+                samples += 1;
+            } else {
+                weights.push(T::zero());
+                merged_y.push(T::zero());
+            }
         }
 
-        if break_serial_correlation {
-            for res in validation_results.iter_mut() {
-                self.update_lambda(res.lambda)?;
+        let mut merged_smoother = WhittakerSmoother::new_with_penalties(
+            &penalties,
+            merged.positions.len(),
+            Some(&merged.positions),
+            Some(&weights),
+        )?;
+        if boundary.is_periodic() {
+            merged_smoother.update_boundary(boundary)?;
+        }
+        if self.penalty_weights.is_some() {
+            merged_smoother.update_penalty_weights(&penalty_weights)?;
+        }
+        let merged_smoothed = merged_smoother.smooth(&merged_y)?;
+
+        Ok(PredictionResult {
+            predicted: merged
+                .query_indices
+                .iter()
+                .map(|index| merged_smoothed[*index])
+                .collect(),
+            smoothed: merged_smoothed
+                .iter()
+                .zip(&merged.is_sample)
+                .filter(|(_, is_sample)| **is_sample)
+                .map(|(z, _)| *z)
+                .collect(),
+        })
+    }
+
+    /// Computes the error of predicting each fold of the data from the rest.
+    ///
+    /// Each fold in turn has its weights set to zero through `update_weights`, so that it is interpolated. The error is the
+    /// square-rooted weighted mean of the squared held-out residuals.
+    fn held_out_error(
+        &self,
+        y_input: &[T],
+        folds: usize,
+        fold_of: impl Fn(usize) -> usize,
+    ) -> Result<T, WhittakerError> {
+        let weights = self
+            .get_weights()
+            .unwrap_or_else(|| vec![T::one(); self.data_length]);
+
+        let mut fold_smoother = self.clone();
+        let mut squared_error = T::zero();
+
+        for fold in 0..folds {
+            let fold_weights = weights
+                .iter()
+                .enumerate()
+                .map(|(i, w)| if fold_of(i) == fold { T::zero() } else { *w })
+                .collect::<Vec<T>>();
+            fold_smoother.update_weights(&fold_weights)?;
+
+            let predicted = fold_smoother.smooth(y_input)?;
+            squared_error += (0..self.data_length)
+                .filter(|i| fold_of(*i) == fold)
+                .map(|i| weights[i] * (y_input[i] - predicted[i]).powi(2))
+                .sum::<T>();
+        }
+
+        Ok((squared_error / weights.iter().sum::<T>()).sqrt())
+    }
+
+    /// Gathers the quantities needed to score a fit.
+    fn fit_statistics(&self, y_input: &[T], smoothed: &[T], hat_diagonal: &[T]) -> FitStatistics {
+        let observations = match self.weights_mat.as_ref() {
+            Some(weights) => weights
+                .diag()
+                .data()
+                .iter()
+                .filter(|w| **w > T::zero())
+                .count(),
+            None => self.data_length,
+        };
+
+        FitStatistics {
+            residual_sum_of_squares: to_f64(self.residual_sum_of_squares(y_input, smoothed)),
+            effective_degrees_of_freedom: to_f64(hat_diagonal.iter().sum::<T>()),
+            observations: observations as f64,
+            penalty: to_f64(self.roughness(smoothed)),
+            log_determinant: self.solver.log_determinant(),
+            log_penalty_weights: self
+                .difference_penalty_weights()
+                .map_or(0.0, |v| v.iter().map(|v| to_f64(v.ln())).sum()),
+            data_length: self.data_length,
+            null_space_dimension: if self.boundary.is_periodic() {
+                // Periodic differences of any order only leave constants unpenalised.
+                self.order.min(1)
+            } else {
+                self.order
+            },
+            lambda: to_f64(self.lambda),
+        }
+    }
+
+    /// Computes the weighted residual sum of squares `Σ w(y - z)²`.
+    fn residual_sum_of_squares(&self, y_input: &[T], smoothed: &[T]) -> T {
+        match self.weights_mat.as_ref() {
+            Some(weights) => y_input
+                .iter()
+                .zip(smoothed)
+                .zip(weights.diag().data())
+                .map(|((y, z), w)| *w * (*y - *z).powi(2))
+                .sum(),
+            None => y_input
+                .iter()
+                .zip(smoothed)
+                .map(|(y, z)| (*y - *z).powi(2))
+                .sum(),
+        }
+    }
+
+    /// Computes the roughness of the smoothed data `||V^½Dz||² + z'Pz / λ`, the full penalty divided by lambda.
+    fn roughness(&self, smoothed: &[T]) -> T {
+        let differences = self.differences(smoothed);
+        let roughness = match self.difference_penalty_weights() {
+            Some(penalty_weights) => differences
+                .iter()
+                .zip(&penalty_weights)
+                .map(|(d, v)| *v * *d * *d)
+                .sum(),
+            None => differences.iter().map(|d| *d * *d).sum(),
+        };
+
+        match self.additional_penalty_mat.as_ref() {
+            Some(additional_penalty) if self.lambda > T::zero() => {
+                roughness
+                    + additional_penalty
+                        .iter()
+                        .map(|(value, (row, column))| *value * smoothed[row] * smoothed[column])
+                        .sum::<T>()
+                        / self.lambda
+            }
+            _ => roughness,
+        }
+    }
+
+    /// Computes the differences `Dz` of the smoothed data which are penalised.
+    fn differences(&self, smoothed: &[T]) -> Vec<T> {
+        let mut differences = vec![T::zero(); self.d_mat.rows()];
+        for (value, (row, column)) in self.d_mat.iter() {
+            differences[row] += *value * smoothed[column];
+        }
+        differences
+    }
+
+    /// Spreads the smoother's penalty weights, if it has any, onto the penalised differences.
+    fn difference_penalty_weights(&self) -> Option<Vec<T>> {
+        self.penalty_weights
+            .as_ref()
+            .map(|v| difference_weights(v, self.order, self.boundary.is_periodic()))
+    }
+
+    /// Computes the diagonal of the hat matrix `(W + λD'D)^-1 W`.
+    fn hat_matrix_diagonal(&self) -> Vec<T> {
+        let inverse_diagonal = self.solver.inverse_diagonal();
+
+        match self.weights_mat.as_ref() {
+            Some(weights) => inverse_diagonal
+                .iter()
+                .zip(weights.diag().data())
+                .map(|(z, w)| *z * *w)
+                .collect(),
+            None => inverse_diagonal,
+        }
+    }
+
+    /// Runs Whittaker-Eilers smoothing for a variety of lambdas and selects the optimally smoothed time series.
+    ///
+    /// This function runs the smoother for lambdas varying from 1e-5 to 1e8 in logarithmic steps of 0.5, the defaults of
+    /// [SmoothOptimalOptions]. It computes the hat/smoother matrix and finds the optimal lambda for the data. If the time-series exhibits
+    /// serial correlation the optimal lambda can be very small and mean the smoothed data doesn't differ from the input data. To avoid
+    /// this, use `break_serial_correlation = true`. To search other lambdas or use another criterion, use `smooth_optimal_with_options`.
+    ///
+    /// It will return the smoothed data, lambda, and cross validation error for each lambda tested!
+    ///
+    /// As each lambda requires a new factorisation and the diagonal of the smoother matrix, this code is much slower than smoothing
+    /// with a known lambda.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed, interpolated, and cross validated for a variety of lambdas.
+    /// * `break_serial_correlation`: Default here should be `true`. Without it data that exhibits serial correlation is barely smoothed.
+    ///
+    /// # Returns:
+    /// [OptimisedSmoothResult]: The smoothed data, lambda, and error for each tested lambda. Calling get_optimal, returns the best smoothed series.
+    pub fn smooth_optimal(
+        &mut self,
+        y_input: &[T],
+        break_serial_correlation: bool,
+    ) -> Result<OptimisedSmoothResult<T>, WhittakerError> {
+        self.smooth_optimal_with_criterion(
+            y_input,
+            break_serial_correlation,
+            SelectionCriterion::CrossValidation,
+        )
+    }
+
+    /// Runs Whittaker-Eilers smoothing for a variety of lambdas and selects the smoothed series which minimises the given criterion.
+    ///
+    /// Tests the same lambdas as `smooth_optimal`, but ranks them with any [SelectionCriterion] rather than only the leave-one-out cross
+    /// validation error. Each result contains its score and effective degrees of freedom.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed, interpolated, and scored for a variety of lambdas.
+    /// * `break_serial_correlation`: Default here should be `true`. Without it data that exhibits serial correlation is barely smoothed.
+    /// * `criterion`: The criterion used to select the optimal lambda.
+    ///
+    /// # Returns:
+    /// [OptimisedSmoothResult]: The smoothed data, lambda, error, and score for each tested lambda. Calling get_optimal, returns the best smoothed series.
+    pub fn smooth_optimal_with_criterion(
+        &mut self,
+        y_input: &[T],
+        break_serial_correlation: bool,
+        criterion: SelectionCriterion,
+    ) -> Result<OptimisedSmoothResult<T>, WhittakerError> {
+        self.smooth_optimal_with_options(
+            y_input,
+            &SmoothOptimalOptions {
+                criterion,
+                serial_correlation: if break_serial_correlation {
+                    SerialCorrelation::Decimate(5)
+                } else {
+                    SerialCorrelation::Ignore
+                },
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Searches continuously for the lambda which minimises the given criterion.
+    ///
+    /// Rather than testing a fixed grid, this runs Brent's method (golden-section search with parabolic interpolation) over log10(lambda)
+    /// between the given bounds. Generally 10-15 evaluations resolve lambda far more finely than the 27 used by `smooth_optimal`. The
+    /// criterion should have a single minimum within the bounds, otherwise a local minimum may be found.
+    ///
+    /// Every evaluated lambda is returned in the order it was evaluated, so the search path can still be plotted.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed, interpolated, and scored.
+    /// * `break_serial_correlation`: Default here should be `true`. Without it data that exhibits serial correlation is barely smoothed.
+    /// * `criterion`: The criterion used to select the optimal lambda.
+    /// * `lambda_bounds`: The smallest and largest lambda to search between. Both must be positive. Try (1e-5, 1e8).
+    /// * `tolerance`: How precisely to locate the optimal lambda, in decades. Try 0.01.
+    /// * `max_evaluations`: The maximum number of lambdas to evaluate. Try 50.
+    ///
+    /// # Returns:
+    /// [OptimisedSmoothResult]: The smoothed data, lambda, error, and score for each evaluated lambda. Calling get_optimal, returns the best smoothed series.
+    pub fn smooth_optimal_continuous(
+        &mut self,
+        y_input: &[T],
+        break_serial_correlation: bool,
+        criterion: SelectionCriterion,
+        lambda_bounds: (f64, f64),
+        tolerance: f64,
+        max_evaluations: usize,
+    ) -> Result<OptimisedSmoothResult<T>, WhittakerError> {
+        self.smooth_optimal_with_options(
+            y_input,
+            &SmoothOptimalOptions {
+                lambdas: LambdaSearch::Brent {
+                    bounds: lambda_bounds,
+                    tolerance,
+                    max_evaluations,
+                },
+                criterion,
+                serial_correlation: if break_serial_correlation {
+                    SerialCorrelation::Decimate(5)
+                } else {
+                    SerialCorrelation::Ignore
+                },
+            },
+        )
+    }
+
+    /// Runs Whittaker-Eilers smoothing for a variety of lambdas, configured by [SmoothOptimalOptions], and selects the optimal one.
+    ///
+    /// The options control which lambdas are tested, the criterion used to rank them, and how serial correlation in the data is broken.
+    /// `smooth_optimal`, `smooth_optimal_with_criterion`, and `smooth_optimal_continuous` are all shorthands for particular options.
+    ///
+    /// Only the lambda of the main penalty term is tuned. The data cannot be subsampled when the smoother has a user-supplied
+    /// [PenaltyTerm::Matrix], so [SerialCorrelation::Decimate] and [SerialCorrelation::IndexMask] return an error for such smoothers.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed, interpolated, and scored for a variety of lambdas.
+    /// * `options`: The lambda search, selection criterion, and serial correlation handling to use.
+    ///
+    /// # Returns:
+    /// [OptimisedSmoothResult]: The smoothed data, lambda, error, and score for each tested lambda. Calling get_optimal, returns the best smoothed series.
+    pub fn smooth_optimal_with_options(
+        &mut self,
+        y_input: &[T],
+        options: &SmoothOptimalOptions,
+    ) -> Result<OptimisedSmoothResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+
+        let criterion = match options.serial_correlation {
+            SerialCorrelation::BlockCrossValidation(blocks) => {
+                SelectionCriterion::BlockedCrossValidation(blocks)
+            }
+            _ => options.criterion,
+        };
+        let mut possible_new_config = match &options.serial_correlation {
+            SerialCorrelation::Decimate(factor) => {
+                if *factor == 0 {
+                    return Err(WhittakerError::InvalidParameter("decimation"));
+                }
+                let mask = (0..self.data_length)
+                    .map(|index| index % factor == 0)
+                    .collect::<Vec<bool>>();
+                Some(self.subsampled(y_input, &mask)?)
+            }
+            SerialCorrelation::IndexMask(mask) => {
+                if mask.len() != self.data_length {
+                    return Err(WhittakerError::LengthMismatch(self.data_length, mask.len()));
+                }
+                Some(self.subsampled(y_input, mask)?)
+            }
+            SerialCorrelation::BlockCrossValidation(_) | SerialCorrelation::Ignore => None,
+        };
+
+        let mut evaluate = |lambda: f64| -> Result<CrossValidationResult<T>, WhittakerError> {
+            let lambda = cast(lambda);
+            match possible_new_config.as_mut() {
+                Some((new_smoother, y)) => {
+                    new_smoother.update_lambda(lambda)?;
+                    new_smoother.smooth_and_score(y, criterion)
+                }
+                None => {
+                    self.update_lambda(lambda)?;
+                    self.smooth_and_score(y_input, criterion)
+                }
+            }
+        };
+
+        let mut validation_results = Vec::new();
+
+        match &options.lambdas {
+            LambdaSearch::Grid(lambdas) => {
+                if lambdas.is_empty()
+                    || lambdas
+                        .iter()
+                        .any(|lambda| lambda.is_nan() || *lambda <= 0.0)
+                {
+                    return Err(WhittakerError::InvalidParameter("lambdas"));
+                }
+                for lambda in lambdas {
+                    validation_results.push(evaluate(*lambda)?);
+                }
+            }
+            LambdaSearch::Range { min, max, steps } => {
+                if min.is_nan() || *min <= 0.0 || max.is_nan() || max <= min {
+                    return Err(WhittakerError::InvalidParameter("lambda_bounds"));
+                }
+                if *steps < 2 {
+                    return Err(WhittakerError::InvalidParameter("steps"));
+                }
+                let start_lambda_log = min.log10();
+                let step = (max.log10() - start_lambda_log) / (steps - 1) as f64;
+                for index in 0..*steps {
+                    validation_results.push(evaluate(
+                        10_f64.powf(start_lambda_log + index as f64 * step),
+                    )?);
+                }
+            }
+            LambdaSearch::Brent {
+                bounds,
+                tolerance,
+                max_evaluations,
+            } => {
+                let (lower, upper) = *bounds;
+                if lower.is_nan() || lower <= 0.0 || upper.is_nan() || upper <= lower {
+                    return Err(WhittakerError::InvalidParameter("lambda_bounds"));
+                }
+                if tolerance.is_nan() || *tolerance <= 0.0 {
+                    return Err(WhittakerError::InvalidParameter("tolerance"));
+                }
+                if *max_evaluations == 0 {
+                    return Err(WhittakerError::InvalidParameter("max_evaluations"));
+                }
+                brent_minimise(
+                    |lambda_log| {
+                        let res = evaluate(10_f64.powf(lambda_log))?;
+                        let score = to_f64(res.score);
+                        validation_results.push(res);
+                        Ok(score)
+                    },
+                    lower.log10(),
+                    upper.log10(),
+                    *tolerance,
+                    *max_evaluations,
+                )?;
+            }
+        }
+
+        let optimal_index = validation_results
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| to_f64(a.score).total_cmp(&to_f64(b.score)))
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+
+        if possible_new_config.is_some() {
+            for res in validation_results.iter_mut() {
+                self.update_lambda(res.lambda)?;
                 res.smoothed = self.smooth(y_input)?;
             }
         }
@@ -528,6 +1487,846 @@ impl WhittakerSmoother {
             optimal_index,
         })
     }
+
+    /// Builds a smoother and data series from the measurements selected by the mask. Used to break serial correlation when selecting lambda.
+    fn subsampled(
+        &self,
+        y_input: &[T],
+        mask: &[bool],
+    ) -> Result<(WhittakerSmoother<T>, Vec<T>), WhittakerError> {
+        let new_y_input = select(y_input, mask);
+
+        let new_length = new_y_input.len();
+
+        let new_x_input = self.x_input.as_ref().map(|x| select(x, mask));
+
+        let new_weights = self
+            .weights_mat
+            .as_ref()
+            .map(|x| select(x.diag().data(), mask));
+
+        let mut penalties = self.get_penalties();
+        penalties[0] = PenaltyTerm::Difference {
+            order: self.order,
+            lambda: T::one(),
+        };
+        if penalties
+            .iter()
+            .any(|term| matches!(term, PenaltyTerm::Matrix { .. }))
+        {
+            // A user-supplied penalty matrix has no equivalent for the subsampled data.
+            return Err(WhittakerError::InvalidParameter("penalties"));
+        }
+
+        let mut new_smoother = WhittakerSmoother::new_with_penalties(
+            &penalties,
+            new_length,
+            new_x_input.as_ref(),
+            new_weights.as_ref(),
+        )?;
+        if self.boundary.is_periodic() {
+            new_smoother.update_boundary(self.boundary)?;
+        }
+        if let Some(penalty_weights) = self.penalty_weights.as_ref() {
+            new_smoother.update_penalty_weights(&select(penalty_weights, mask))?;
+        }
+        Ok((new_smoother, new_y_input))
+    }
+
+    /// Traces out the L-curve by smoothing for each of the given lambdas and selects lambda at its corner.
+    ///
+    /// The L-curve plots the log of the residual norm `||W^½(y - z)||` against the log of the roughness penalty norm `||Dz||`. Small
+    /// lambdas lie on its steep arm, where the roughness drops quickly for little extra residual, and large lambdas on its flat arm. The
+    /// corner balances the two and is a useful alternative where cross validation undersmooths. It is found either by maximum curvature
+    /// or by Eilers' V-curve. See [CornerMethod].
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed.
+    /// * `lambdas`: The lambdas to test, in increasing order. Logarithmically spaced lambdas, such as 1e-2 to 1e8 in steps of 0.1 decades, work well.
+    /// * `method`: The method used to find the corner.
+    ///
+    /// # Returns:
+    /// [LCurveResult]: The smoothed data, residual norm, and penalty norm for each lambda as well as for the corner.
+    pub fn l_curve(
+        &mut self,
+        y_input: &[T],
+        lambdas: &[T],
+        method: CornerMethod,
+    ) -> Result<LCurveResult<T>, WhittakerError> {
+        let minimum_lambdas = match method {
+            CornerMethod::MaximumCurvature => 3,
+            CornerMethod::VCurve => 2,
+        };
+        if lambdas.len() < minimum_lambdas
+            || lambdas[0].is_nan()
+            || lambdas[0] <= T::zero()
+            || lambdas
+                .windows(2)
+                .any(|pair| pair[1].is_nan() || pair[1] <= pair[0])
+        {
+            return Err(WhittakerError::InvalidParameter("lambdas"));
+        }
+
+        let points = lambdas
+            .iter()
+            .map(|lambda| self.l_curve_point(y_input, *lambda))
+            .collect::<Result<Vec<LCurvePoint<T>>, WhittakerError>>()?;
+
+        let corner = match method {
+            CornerMethod::MaximumCurvature => points[maximum_curvature_index(&points)].clone(),
+            CornerMethod::VCurve => {
+                let index = v_curve_index(&points);
+                self.l_curve_point(y_input, (lambdas[index] * lambdas[index + 1]).sqrt())?
+            }
+        };
+
+        Ok(LCurveResult { points, corner })
+    }
+
+    fn l_curve_point(
+        &mut self,
+        y_input: &[T],
+        lambda: T,
+    ) -> Result<LCurvePoint<T>, WhittakerError> {
+        self.update_lambda(lambda)?;
+        let smoothed = self.smooth(y_input)?;
+
+        Ok(LCurvePoint {
+            lambda,
+            residual_norm: self.residual_sum_of_squares(y_input, &smoothed).sqrt(),
+            penalty_norm: self.roughness(&smoothed).sqrt(),
+            smoothed,
+        })
+    }
+
+    /// Run robust Whittaker-Eilers smoothing which resists outliers.
+    ///
+    /// Outliers are handled through iteratively reweighted least squares. After each smooth, the residuals are scaled by their median absolute
+    /// deviation and passed through the chosen [RobustLoss] to produce a robust weight for every measurement. The data is then smoothed again with
+    /// the robust weights multiplied into any weights the smoother already has. This repeats until the smoothed series changes by less than
+    /// `tolerance` (relative to its norm) or `max_iterations` is reached.
+    ///
+    /// The smoother's own weights are restored before returning, so it can continue to be reused.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be robustly smoothed and interpolated by the Whittaker-Eilers smoother.
+    /// * `loss`: The loss function used to downweight large residuals.
+    /// * `max_iterations`: The maximum number of reweighting iterations. Generally 10-20 is more than enough.
+    /// * `tolerance`: The relative change in the smoothed series below which the iterations are stopped.
+    ///
+    /// # Returns:
+    /// [RobustSmoothResult]: The smoothed data, final robust weights, number of iterations, and whether the iterations converged. Errors if
+    /// the loss' tuning constant isn't finite and positive.
+    pub fn smooth_robust(
+        &mut self,
+        y_input: &[T],
+        loss: RobustLoss,
+        max_iterations: usize,
+        tolerance: T,
+    ) -> Result<RobustSmoothResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+        let tuning_constant = loss.tuning_constant();
+        if !(tuning_constant.is_finite() && tuning_constant > 0.0) {
+            return Err(WhittakerError::InvalidParameter("tuning_constant"));
+        }
+
+        self.with_weights_restored(|smoother, prior_weights| {
+            let mut smoothed = smoother.smooth(y_input)?;
+            let mut weights = vec![T::one(); smoother.data_length];
+            let mut iterations = 0;
+            let mut converged = false;
+
+            while iterations < max_iterations {
+                iterations += 1;
+
+                let residuals = y_input
+                    .iter()
+                    .zip(&smoothed)
+                    .map(|(y, z)| *y - *z)
+                    .collect::<Vec<T>>();
+
+                weights = robust_weights(&residuals, prior_weights, loss);
+
+                smoother.update_weights(
+                    &weights
+                        .iter()
+                        .zip(prior_weights)
+                        .map(|(a, b)| *a * *b)
+                        .collect::<Vec<T>>(),
+                )?;
+
+                let new_smoothed = smoother.smooth(y_input)?;
+
+                let change = new_smoothed
+                    .iter()
+                    .zip(&smoothed)
+                    .map(|(a, b)| (*a - *b).powi(2))
+                    .sum::<T>()
+                    .sqrt();
+                let norm = smoothed.iter().map(|a| a.powi(2)).sum::<T>().sqrt();
+
+                smoothed = new_smoothed;
+
+                if change <= tolerance * norm {
+                    converged = true;
+                    break;
+                }
+            }
+
+            Ok(RobustSmoothResult {
+                smoothed,
+                robust_weights: weights,
+                iterations,
+                converged,
+            })
+        })
+    }
+
+    /// Estimate the baseline of a signal using asymmetric least squares.
+    ///
+    /// Baseline correction, common in spectroscopy and chromatography, repeatedly smooths the data with asymmetric weights so that
+    /// the smoothed series hugs the bottom of the signal rather than passing through its peaks. The asymmetric weights are multiplied
+    /// into any weights the smoother already has and the smoother's own weights are restored before returning. Large lambdas (1e5 - 1e8)
+    /// and an order of 2 work well for most baselines.
+    ///
+    /// # Arguments
+    /// * `y_input`: The signal for which the baseline will be estimated.
+    /// * `method`: The asymmetric least squares variant. See [BaselineMethod].
+    /// * `max_iterations`: The maximum number of reweighting iterations.
+    /// * `tolerance`: The relative change in weights below which the iterations are stopped. For [BaselineMethod::AirPls] this is instead
+    ///   the fraction of the total signal which may lie below the baseline.
+    ///
+    /// # Returns:
+    /// [BaselineResult]: The baseline, the baseline-corrected signal, the number of iterations, and whether the iterations converged.
+    pub fn baseline(
+        &mut self,
+        y_input: &[T],
+        method: BaselineMethod,
+        max_iterations: usize,
+        tolerance: T,
+    ) -> Result<BaselineResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+        if let BaselineMethod::Als(p) = method {
+            if !(p > 0.0 && p < 1.0) {
+                return Err(WhittakerError::InvalidParameter("p"));
+            }
+        }
+
+        self.with_weights_restored(|smoother, prior_weights| {
+            let mut baseline = smoother.smooth(y_input)?;
+            let mut weights = vec![T::one(); smoother.data_length];
+            let mut iterations = 0;
+            let mut converged = false;
+
+            while iterations < max_iterations {
+                match asymmetric_weights(
+                    method,
+                    y_input,
+                    &baseline,
+                    &weights,
+                    iterations + 1,
+                    tolerance,
+                ) {
+                    Some(new_weights) => weights = new_weights,
+                    None => {
+                        converged = true;
+                        break;
+                    }
+                }
+                iterations += 1;
+
+                smoother.update_weights(
+                    &weights
+                        .iter()
+                        .zip(prior_weights)
+                        .map(|(a, b)| *a * *b)
+                        .collect::<Vec<T>>(),
+                )?;
+                baseline = smoother.smooth(y_input)?;
+            }
+
+            let corrected = y_input
+                .iter()
+                .zip(&baseline)
+                .map(|(y, z)| *y - *z)
+                .collect();
+
+            Ok(BaselineResult {
+                baseline,
+                corrected,
+                iterations,
+                converged,
+            })
+        })
+    }
+
+    /// Run Whittaker-Eilers smoothing with lower and/or upper bounds on the smoothed data.
+    ///
+    /// Concentrations and counts can be kept non-negative, even where gaps are interpolated, by passing a lower bound of zero. Bounds
+    /// are given per point and an infinite bound leaves a point unconstrained. The bounds are enforced with a penalty iteration: after
+    /// each smooth, the points beyond a bound are pinned to it with a very large weight and the data is smoothed again. Points are
+    /// released once pinning is no longer needed to hold them within the bounds. This repeats until the set of pinned points stops
+    /// changing, which usually takes only a few iterations.
+    ///
+    /// The smoothed data is finally clamped into the bounds, removing the tiny violations the penalty leaves behind. The smoother's own
+    /// weights are restored before returning.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed.
+    /// * `lower`: The lowest value the smoothed data may take at each point.
+    /// * `upper`: The highest value the smoothed data may take at each point. Must not be below `lower`.
+    /// * `max_iterations`: The maximum number of pinning iterations.
+    ///
+    /// # Returns:
+    /// [BoundedSmoothResult]: The smoothed data, which points are on a bound, the number of iterations, and whether the iterations converged.
+    pub fn smooth_bounded(
+        &mut self,
+        y_input: &[T],
+        lower: Option<&[T]>,
+        upper: Option<&[T]>,
+        max_iterations: usize,
+    ) -> Result<BoundedSmoothResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+        for bound in [lower, upper].into_iter().flatten() {
+            if bound.len() != self.data_length {
+                return Err(WhittakerError::LengthMismatch(
+                    self.data_length,
+                    bound.len(),
+                ));
+            }
+            if bound.iter().any(|b| b.is_nan()) {
+                return Err(WhittakerError::InvalidParameter("bounds"));
+            }
+        }
+        if let (Some(lower), Some(upper)) = (lower, upper) {
+            if lower.iter().zip(upper).any(|(l, u)| l > u) {
+                return Err(WhittakerError::InvalidParameter("bounds"));
+            }
+        }
+
+        self.with_weights_restored(|smoother, prior_weights| {
+            let mut smoothed = smoother.smooth(y_input)?;
+            let mut on_lower_bound = vec![false; smoother.data_length];
+            let mut on_upper_bound = vec![false; smoother.data_length];
+            let mut iterations = 0;
+            let mut converged = false;
+
+            while iterations < max_iterations {
+                let below = beyond_bound(&smoothed, lower, &on_lower_bound, |z, l| z < l);
+                let above = beyond_bound(&smoothed, upper, &on_upper_bound, |z, u| z > u);
+                if below == on_lower_bound && above == on_upper_bound {
+                    converged = true;
+                    break;
+                }
+                on_lower_bound = below;
+                on_upper_bound = above;
+                iterations += 1;
+
+                let targets = (0..smoother.data_length).map(|i| {
+                    if on_lower_bound[i] {
+                        lower.map(|lower| lower[i])
+                    } else if on_upper_bound[i] {
+                        upper.map(|upper| upper[i])
+                    } else {
+                        None
+                    }
+                });
+                let (weights, pinned_y) = pinned(y_input, prior_weights, targets);
+
+                smoother.update_weights(&weights)?;
+                smoothed = smoother.smooth(&pinned_y)?;
+            }
+
+            for (i, z) in smoothed.iter_mut().enumerate() {
+                if let Some(lower) = lower {
+                    *z = z.max(lower[i]);
+                }
+                if let Some(upper) = upper {
+                    *z = z.min(upper[i]);
+                }
+            }
+
+            Ok(BoundedSmoothResult {
+                smoothed,
+                on_lower_bound,
+                on_upper_bound,
+                iterations,
+                converged,
+            })
+        })
+    }
+
+    /// Run Whittaker-Eilers smoothing constrained to be monotone, convex, or concave.
+    ///
+    /// Calibration curves and cumulative quantities can be kept monotone and dose-response curves concave. The constraint is enforced with
+    /// Eilers' asymmetric penalty: after each smooth, the first or second differences which break the constraint are given a very large
+    /// extra penalty and the data is smoothed again. This repeats until the set of penalised differences stops changing. For unequally
+    /// spaced data, divided differences are used, just as for the smoothness penalty.
+    ///
+    /// Each iteration factorises a new system, so this costs a few times as much as `smooth`. The smoother itself is not changed.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed.
+    /// * `shape`: The shape to constrain the smoothed data to. See [ShapeConstraint].
+    /// * `max_iterations`: The maximum number of iterations. Generally 10-20 is more than enough.
+    ///
+    /// # Returns:
+    /// [ShapeSmoothResult]: The smoothed data, the number of iterations, and whether the iterations converged.
+    pub fn smooth_shape_constrained(
+        &self,
+        y_input: &[T],
+        shape: ShapeConstraint,
+        max_iterations: usize,
+    ) -> Result<ShapeSmoothResult<T>, WhittakerError> {
+        if y_input.len() != self.data_length {
+            return Err(WhittakerError::LengthMismatch(
+                self.data_length,
+                y_input.len(),
+            ));
+        }
+        let difference_order = shape.difference_order();
+        if self.data_length <= difference_order {
+            return Err(WhittakerError::DataTooShort(
+                self.data_length,
+                difference_order,
+            ));
+        }
+
+        let difference_mat = match &self.x_input {
+            Some(x) => ddmat(x, x.len(), difference_order),
+            None => diff_no_ddmat(&self.e_mat, difference_order),
+        }
+        .to_csr();
+        let sign = shape.sign::<T>();
+
+        let prior_weights = self
+            .get_weights()
+            .unwrap_or_else(|| vec![T::one(); self.data_length]);
+        let kappa = shape_weight(&prior_weights);
+        let weighted_y = y_input
+            .iter()
+            .zip(&prior_weights)
+            .map(|(y, w)| *y * *w)
+            .collect::<Vec<T>>();
+
+        let mut smoothed = self.smooth(y_input)?;
+        let zeros = vec![T::zero(); difference_mat.rows()];
+        let mut penalised = vec![false; difference_mat.rows()];
+        let mut iterations = 0;
+        let mut converged = false;
+
+        while iterations < max_iterations {
+            let mut differences = vec![T::zero(); difference_mat.rows()];
+            for (value, (row, column)) in difference_mat.iter() {
+                differences[row] += sign * *value * smoothed[column];
+            }
+            let broken = beyond_bound(&differences, Some(&zeros), &penalised, |d, b| d < b);
+            if broken == penalised {
+                converged = true;
+                break;
+            }
+            penalised = broken;
+            iterations += 1;
+
+            let mut shape_penalty = difference_mat.clone();
+            for (row, mut values) in shape_penalty.outer_iterator_mut().enumerate() {
+                let scale = if penalised[row] { kappa } else { T::zero() };
+                for (_, value) in values.iter_mut() {
+                    *value *= scale;
+                }
+            }
+            let shape_penalty = &difference_mat.transpose_view() * &shape_penalty;
+
+            let to_solve = csmat_binop(
+                self.to_solve.to_csr().view(),
+                shape_penalty.to_csr().view(),
+                |a, b| *a + *b,
+            );
+            smoothed = factorise(&to_solve)?.solve(&weighted_y);
+        }
+
+        Ok(ShapeSmoothResult {
+            smoothed,
+            iterations,
+            converged,
+        })
+    }
+
+    /// Run Whittaker-Eilers smoothing with penalty weights estimated from the local roughness of the data.
+    ///
+    /// A first pass smooths the data as configured. The squared differences of that fit, averaged over `window` points either side of
+    /// each point, give its local roughness. The penalty weight of each point is then the square root of the median roughness over its
+    /// local roughness, so sharp features are smoothed less and flat stretches more. The weights are scaled to a geometric mean of 1, so
+    /// `lambda` keeps roughly the same meaning, and multiplied into any penalty weights the smoother already has. The data is smoothed
+    /// again with them.
+    ///
+    /// The smoother's own penalty weights are restored before returning. Pass the returned penalty weights to `update_penalty_weights`
+    /// to keep smoothing similar data with them, or to select `lambda` for them with `smooth_optimal`.
+    ///
+    /// # Arguments
+    /// * `y_input`: The values which are to be smoothed.
+    /// * `window`: The number of points either side of each point over which the roughness is averaged. Try 5-20.
+    ///
+    /// # Returns:
+    /// [AdaptiveSmoothResult]: The smoothed data and the penalty weights it was smoothed with.
+    pub fn smooth_adaptive(
+        &mut self,
+        y_input: &[T],
+        window: usize,
+    ) -> Result<AdaptiveSmoothResult<T>, WhittakerError> {
+        let first_pass = self.smooth(y_input)?;
+
+        let original_penalty_weights = self.penalty_weights.clone();
+        let penalty_weights = roughness_penalty_weights(
+            &self.differences(&first_pass),
+            self.order,
+            window,
+            self.boundary.is_periodic(),
+        )
+        .iter()
+        .zip(
+            original_penalty_weights
+                .clone()
+                .unwrap_or_else(|| vec![T::one(); self.data_length]),
+        )
+        .map(|(a, b)| *a * b)
+        .collect::<Vec<T>>();
+
+        self.update_penalty_weights(&penalty_weights)?;
+        let smoothed = self.smooth(y_input);
+        self.restore_penalty_weights(original_penalty_weights)?;
+
+        Ok(AdaptiveSmoothResult {
+            smoothed: smoothed?,
+            penalty_weights,
+        })
+    }
+
+    /// Returns a copy of the smoother's current weights, if it has any.
+    fn get_weights(&self) -> Option<Vec<T>> {
+        self.weights_mat.as_ref().map(|x| x.diag().data().to_vec())
+    }
+
+    /// Puts back penalty weights previously taken from the smoother after they have been temporarily changed.
+    fn restore_penalty_weights(
+        &mut self,
+        penalty_weights: Option<Vec<T>>,
+    ) -> Result<(), WhittakerError> {
+        self.penalty_weights = penalty_weights;
+        self.update_lambda(self.lambda)
+    }
+
+    /// Runs `iterate`, which may temporarily change the smoother's weights, and always puts the original weights back afterwards, even if
+    /// `iterate` fails. `iterate` is given the original weights, or ones if the smoother has none.
+    fn with_weights_restored<R>(
+        &mut self,
+        iterate: impl FnOnce(&mut Self, &[T]) -> Result<R, WhittakerError>,
+    ) -> Result<R, WhittakerError> {
+        let original_weights = self.get_weights();
+        let prior_weights = original_weights
+            .clone()
+            .unwrap_or_else(|| vec![T::one(); self.data_length]);
+
+        let result = iterate(self, &prior_weights);
+        let restored = self.restore_weights(original_weights);
+
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
+    /// Puts back weights previously taken with `get_weights` after they have been temporarily changed.
+    fn restore_weights(&mut self, weights: Option<Vec<T>>) -> Result<(), WhittakerError> {
+        match weights {
+            Some(weights) => self.update_weights(&weights),
+            None => {
+                self.weights_mat = None;
+                self.update_lambda(self.lambda)
+            }
+        }
+    }
+}
+
+/// Builds the system `W + λD'VD + P` solved by the smoother, where `P` is the sum of any additional penalty terms. Without penalty
+/// weights, `V` is the identity.
+fn system_matrix<T: WhittakerFloat>(
+    weights: &CsMat<T>,
+    d_mat: &CsMat<T>,
+    penalty_weights: Option<&[T]>,
+    additional_penalty: Option<&CsMat<T>>,
+    lambda: T,
+) -> CsMat<T> {
+    let penalty = penalty_matrix(d_mat, penalty_weights, additional_penalty, lambda);
+
+    csmat_binop(weights.to_csr().view(), penalty.view(), |a, b| *a + *b)
+}
+
+/// Builds the penalty `λD'VD + P` of the system, in CSR storage.
+fn penalty_matrix<T: WhittakerFloat>(
+    d_mat: &CsMat<T>,
+    penalty_weights: Option<&[T]>,
+    additional_penalty: Option<&CsMat<T>>,
+    lambda: T,
+) -> CsMat<T> {
+    let penalty = match penalty_weights {
+        Some(penalty_weights) => {
+            let mut weighted_d_mat = d_mat.to_csr();
+            for (row, mut values) in weighted_d_mat.outer_iterator_mut().enumerate() {
+                for (_, value) in values.iter_mut() {
+                    *value *= penalty_weights[row];
+                }
+            }
+            &d_mat.transpose_view() * &weighted_d_mat
+        }
+        None => &d_mat.transpose_view() * d_mat,
+    }
+    .map(|value| *value * lambda);
+
+    match additional_penalty {
+        Some(additional_penalty) => csmat_binop(
+            penalty.to_csr().view(),
+            additional_penalty.to_csr().view(),
+            |a, b| *a + *b,
+        ),
+        None => penalty.to_csr(),
+    }
+}
+
+/// Builds a diagonal matrix, such as the weights matrix `W`, from its diagonal.
+fn diagonal_matrix<T: WhittakerFloat>(diagonal: &[T]) -> CsMat<T> {
+    let diags = (0..diagonal.len() + 1).collect::<Vec<usize>>();
+
+    CsMat::new_csc(
+        (diagonal.len(), diagonal.len()),
+        diags[..].to_vec(),
+        diags[..diagonal.len()].to_vec(),
+        diagonal.to_vec(),
+    )
+}
+
+/// Takes the `lambda` and `order` of the main term of a penalty, which must be a difference term with a valid lambda.
+fn main_term<T: WhittakerFloat>(
+    penalties: &[PenaltyTerm<T>],
+) -> Result<(T, usize), WhittakerError> {
+    match penalties.first() {
+        Some(PenaltyTerm::Difference { order, lambda }) => {
+            validate_lambda(*lambda)?;
+            Ok((*lambda, *order))
+        }
+        _ => Err(WhittakerError::InvalidParameter("penalties")),
+    }
+}
+
+impl<T: WhittakerFloat> Solver<T> {
+    fn solve(&self, rhs: &[T]) -> Vec<T> {
+        match self {
+            Solver::Banded(ldl) => ldl.solve(rhs),
+            Solver::Sparse { ldl, .. } => ldl.solve(rhs),
+        }
+    }
+
+    /// Factorises the system again after its values have changed.
+    ///
+    /// If the sparse factorisation is in use and the sparsity pattern of the system is unchanged, only the numeric factorisation is
+    /// redone. The fill-in reducing permutation and symbolic analysis are reused from before.
+    fn refactorise(
+        &mut self,
+        previous: &CsMat<T>,
+        to_solve: &CsMat<T>,
+    ) -> Result<(), WhittakerError> {
+        if let Solver::Sparse { ldl, .. } = self {
+            if same_pattern(previous, to_solve) {
+                // A failed update leaves the factor partly updated, so it is rebuilt from scratch instead.
+                if ldl.update(to_solve.view()).is_ok() {
+                    return Ok(());
+                }
+            }
+        }
+        *self = factorise(to_solve)?;
+        Ok(())
+    }
+
+    /// Solves in place for several right hand sides stored row by row, with rows in the order of the factorised system.
+    fn solve_block(&self, block: &mut [T], columns: usize) {
+        match self {
+            Solver::Banded(ldl) => ldl.solve_block(block, columns),
+            Solver::Sparse { ldl, .. } => {
+                let l = ldl.l();
+                for (column, entries) in l.outer_iterator().enumerate() {
+                    for (row, value) in entries.iter() {
+                        subtract_row(block, columns, row, column, *value);
+                    }
+                }
+                divide_rows(block, columns, ldl.d());
+                for (column, entries) in l.outer_iterator().enumerate().rev() {
+                    for (row, value) in entries.iter() {
+                        subtract_row(block, columns, column, row, *value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The row of the data that row `row` of the factorised system corresponds to, after any fill-in reducing permutation.
+    fn original_row(&self, row: usize) -> usize {
+        match self {
+            Solver::Banded(_) => row,
+            Solver::Sparse { perm, .. } => perm.at(row),
+        }
+    }
+
+    /// Computes the diagonal of `(W + λD'D)^-1`.
+    fn inverse_diagonal(&self) -> Vec<T> {
+        match self {
+            Solver::Banded(ldl) => ldl.inverse_diagonal(),
+            Solver::Sparse { ldl, perm } => inverse_diagonal(ldl, perm),
+        }
+    }
+
+    /// Computes `log|W + λD'D|` from the diagonal of the factorisation.
+    fn log_determinant(&self) -> f64 {
+        let d = match self {
+            Solver::Banded(ldl) => ldl.d(),
+            Solver::Sparse { ldl, .. } => ldl.d(),
+        };
+        d.iter().map(|d| to_f64(*d).ln()).sum()
+    }
+}
+
+/// Computes the LDLᵀ factorisation of the system to solve.
+///
+/// The banded factorisation is used whenever storing the bands takes no more memory than the sparse matrix. Otherwise, or if it
+/// meets a non-positive pivot, the sparse factorisation with a fill-in reducing permutation is used instead.
+fn factorise<T: WhittakerFloat>(to_solve: &CsMat<T>) -> Result<Solver<T>, WhittakerError> {
+    let bandwidth = bandwidth(to_solve);
+
+    if to_solve.rows() * (bandwidth + 1) <= to_solve.nnz() {
+        if let Some(ldl) = BandedLdl::new(to_solve, bandwidth) {
+            return Ok(Solver::Banded(ldl));
+        }
+    }
+
+    let perm = Ldl::new()
+        .fill_in_reduction(ReverseCuthillMcKee)
+        .perm(to_solve.view());
+
+    let ldl = LdlNumeric::new_perm(to_solve.view(), perm.clone(), CheckSymmetry)
+        .map_err(WhittakerError::SolverError)?;
+
+    Ok(Solver::Sparse {
+        ldl: Box::new(ldl),
+        perm,
+    })
+}
+
+/// Computes the sparse LDLᵀ factorisation of a system from the symbolic analysis of another system with the same sparsity pattern.
+fn factorise_numeric<T: WhittakerFloat>(
+    to_solve: &CsMat<T>,
+    symbolic: LdlSymbolic<usize>,
+    perm: &PermOwned,
+) -> Result<Solver<T>, WhittakerError> {
+    let ldl = symbolic
+        .factor(to_solve.view())
+        .map_err(WhittakerError::SolverError)?;
+
+    Ok(Solver::Sparse {
+        ldl: Box::new(ldl),
+        perm: perm.clone(),
+    })
+}
+
+/// Whether two systems share a sparsity pattern, so that one's symbolic analysis can be used to factorise the other.
+fn same_pattern<T>(a: &CsMat<T>, b: &CsMat<T>) -> bool {
+    a.indptr().raw_storage() == b.indptr().raw_storage() && a.indices() == b.indices()
+}
+
+/// Checks that a smoothing constant is finite and not negative.
+pub(crate) fn validate_lambda<T: WhittakerFloat>(lambda: T) -> Result<(), WhittakerError> {
+    if !lambda.is_finite() || lambda < T::zero() {
+        return Err(WhittakerError::InvalidParameter("lambda"));
+    }
+    Ok(())
+}
+
+/// Checks that weights match the data length, are finite and not negative, and are not all zero.
+pub(crate) fn validate_weights<T: WhittakerFloat>(
+    weights: &[T],
+    data_length: usize,
+) -> Result<(), WhittakerError> {
+    if data_length != weights.len() {
+        return Err(WhittakerError::LengthMismatch(data_length, weights.len()));
+    }
+    if let Some(i) = weights
+        .iter()
+        .position(|weight| !weight.is_finite() || *weight < T::zero())
+    {
+        return Err(WhittakerError::InvalidValue("weights", i));
+    }
+    if weights.iter().all(|weight| weight.is_zero()) {
+        return Err(WhittakerError::AllWeightsZero);
+    }
+    Ok(())
+}
+
+/// Checks that every value of an input is finite, giving the index of the first that isn't.
+fn validate_finite<T: WhittakerFloat>(
+    values: &[T],
+    name: &'static str,
+) -> Result<(), WhittakerError> {
+    match values.iter().position(|value| !value.is_finite()) {
+        Some(i) => Err(WhittakerError::InvalidValue(name, i)),
+        None => Ok(()),
+    }
+}
+
+/// Checks that an x input matches the data length, is monotonically increasing, and is not sampled too closely.
+pub(crate) fn validate_x_input<T: WhittakerFloat>(
+    x_input: &[T],
+    data_length: usize,
+) -> Result<(), WhittakerError> {
+    if data_length != x_input.len() {
+        return Err(WhittakerError::LengthMismatch(data_length, x_input.len()));
+    }
+    validate_finite(x_input, "x_input")?;
+
+    for i in 0..data_length.saturating_sub(1) {
+        if x_input[i] >= x_input[i + 1] {
+            return Err(WhittakerError::NotMonotonicallyIncreasing(i));
+        }
+        if (x_input[i] - x_input[i + 1]).abs() < cast(WHITTAKER_X_EPSILON) {
+            return Err(WhittakerError::SampleRateError(i));
+        }
+    }
+    Ok(())
+}
+
+/// Differencing matrix of order d for the given sampling and boundary conditions.
+pub(crate) fn difference_matrix<T: WhittakerFloat>(
+    x_input: Option<&[T]>,
+    size: usize,
+    d: usize,
+    boundary: &Boundary<T>,
+) -> CsMat<T> {
+    match (boundary, x_input) {
+        (Boundary::Open, Some(x)) => ddmat(x, size, d),
+        (Boundary::Open, None) => diff_no_ddmat(&CsMat::eye(size), d),
+        (Boundary::Periodic { period }, x) => periodic_ddmat(x.zip(*period), size, d),
+    }
 }
 
 /// Dividing differencing matrix of order d
@@ -536,11 +2335,11 @@ impl WhittakerSmoother {
 /// * `x`: Sampling positions.
 /// * `size`: Length og the data.
 /// * `d`: order of differences.
-fn ddmat(x: &[f64], size: usize, d: usize) -> CsMat<f64> {
+pub(crate) fn ddmat<T: WhittakerFloat>(x: &[T], size: usize, d: usize) -> CsMat<T> {
     if d == 0 {
         return CsMat::eye(size);
     } else {
-        let dx: Vec<f64> = x.windows(d + 1).map(|t| 1_f64 / (t[d] - t[0])).collect();
+        let dx: Vec<T> = x.windows(d + 1).map(|t| T::one() / (t[d] - t[0])).collect();
 
         let ind: Vec<usize> = (0..(size - d) + 1).collect();
 
@@ -553,13 +2352,13 @@ fn ddmat(x: &[f64], size: usize, d: usize) -> CsMat<f64> {
 }
 
 // Finds the difference between adjacent elements of a sparse matrix
-fn diff(e: &CsMat<f64>) -> CsMat<f64> {
+fn diff<T: WhittakerFloat>(e: &CsMat<T>) -> CsMat<T> {
     let e1 = e.slice_outer(0..e.rows() - 1);
     let e2 = e.slice_outer(1..e.rows());
-    return &e2 - &e1;
+    csmat_binop(e2, e1, |a, b| *a - *b)
 }
 // Dividing difference matrix for equally spaced data.
-fn diff_no_ddmat(e: &CsMat<f64>, d: usize) -> CsMat<f64> {
+pub(crate) fn diff_no_ddmat<T: WhittakerFloat>(e: &CsMat<T>, d: usize) -> CsMat<T> {
     if d == 0 {
         return e.clone();
     } else {
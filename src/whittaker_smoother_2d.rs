@@ -0,0 +1,294 @@
+use crate::errors::WhittakerError;
+use crate::whittaker_smoother::{
+    ddmat, diff_no_ddmat, validate_lambda, validate_weights, validate_x_input,
+};
+use nalgebra::{DMatrix, DVector};
+
+use sprs::FillInReduction::ReverseCuthillMcKee;
+use sprs::SymmetryCheck::CheckSymmetry;
+use sprs::{kronecker_product, CsMat};
+use sprs_ldl::{Ldl, LdlNumeric};
+
+/// Whittaker-Eilers Smoother and Interpolator for two-dimensional gridded data.
+///
+/// Smooths data on a grid, such as an image or a time-by-wavelength matrix, using the Kronecker-sum penalty
+/// `λ_r (D_r'D_r ⊗ I) + λ_c (I ⊗ D_c'D_c)`. Each axis has its own `lambda`, `order`, and optional sample positions. Data is passed in
+/// and returned as a flat row-major slice, so the element at `(row, column)` lives at `row * columns + column`. All pairs of
+/// parameters are given as `(rows, columns)`.
+///
+/// When the smoother has no weights and neither axis is longer than 500 points, the grid is smoothed with Eilers' array
+/// algorithm: each axis' penalty is diagonalised once and the data is smoothed with dense matrix products of the size of a single
+/// row or column. This avoids solving the full `rows * columns` system and makes updating the lambdas almost free. The
+/// diagonalisation is a dense eigendecomposition costing O(n³) in the axis length, so longer axes fall back to the sparse solver.
+/// Weights, even separable ones, cannot be diagonalised alongside the penalties, so weighted grids never take this fast path. Whenever
+/// weights are provided, even if they are all one, the full sparse system is solved instead so that gaps can be interpolated.
+#[derive(Clone)]
+pub struct WhittakerSmoother2D {
+    lambdas: (f64, f64),
+    orders: (usize, usize),
+    shape: (usize, usize),
+    x_inputs: (Option<Vec<f64>>, Option<Vec<f64>>),
+    row_penalty: CsMat<f64>,
+    column_penalty: CsMat<f64>,
+    weights: Option<Vec<f64>>,
+    solver: Solver2D,
+}
+
+/// The longest axis, in rows or columns, for which the array algorithm is used.
+const ARRAY_MAX_AXIS_LENGTH: usize = 500;
+
+#[derive(Clone)]
+enum Solver2D {
+    Sparse(LdlNumeric<f64, usize>),
+    Array {
+        row_vectors: DMatrix<f64>,
+        row_values: DVector<f64>,
+        column_vectors: DMatrix<f64>,
+        column_values: DVector<f64>,
+    },
+}
+
+impl WhittakerSmoother2D {
+    /// Create a new two-dimensional Whittaker-Eilers smoother and interpolator.
+    ///
+    /// As with the one-dimensional [crate::WhittakerSmoother], `lambdas` control the smoothness along each axis and `orders` the order of the
+    /// penalties. Weights can be given to every grid point, with a weight of 0 resulting in interpolation.
+    ///
+    /// # Arguments:
+    /// * `lambdas`: The smoothing strength along the rows and columns of the grid.
+    /// * `orders`: The order of the filter along the rows and columns of the grid.
+    /// * `shape`: The number of rows and columns in the grid.
+    /// * `x_inputs`: The positions of the rows and of the columns. Used to smooth unequally spaced grids. Must be monotonically increasing.
+    /// * `weights`: The weight of each grid point in row-major order.
+    pub fn new(
+        lambdas: (f64, f64),
+        orders: (usize, usize),
+        shape: (usize, usize),
+        x_inputs: (Option<&Vec<f64>>, Option<&Vec<f64>>),
+        weights: Option<&Vec<f64>>,
+    ) -> Result<WhittakerSmoother2D, WhittakerError> {
+        validate_lambda(lambdas.0)?;
+        validate_lambda(lambdas.1)?;
+
+        let row_penalty = axis_penalty(shape.0, orders.0, x_inputs.0)?;
+        let column_penalty = axis_penalty(shape.1, orders.1, x_inputs.1)?;
+
+        if let Some(weights) = weights {
+            validate_weights(weights, shape.0 * shape.1)?;
+        }
+
+        let solver = build_solver(shape, lambdas, &row_penalty, &column_penalty, weights)?;
+
+        Ok(WhittakerSmoother2D {
+            lambdas,
+            orders,
+            shape,
+            x_inputs: (x_inputs.0.cloned(), x_inputs.1.cloned()),
+            row_penalty,
+            column_penalty,
+            weights: weights.cloned(),
+            solver,
+        })
+    }
+
+    /// Retrieve the smoother's current lambdas as `(rows, columns)`.
+    pub fn get_lambdas(&self) -> (f64, f64) {
+        self.lambdas
+    }
+
+    /// Retrieve the smoother's current orders as `(rows, columns)`.
+    pub fn get_orders(&self) -> (usize, usize) {
+        self.orders
+    }
+
+    /// Retrieve the shape of the smoother's grid as `(rows, columns)`.
+    pub fn get_shape(&self) -> (usize, usize) {
+        self.shape
+    }
+
+    /// Updates the smoothing constants of the smoother.
+    ///
+    /// When the smoother uses the array algorithm this does not require any refactorisation.
+    ///
+    /// # Arguments:
+    /// * `lambdas`: The smoothing strength along the rows and columns of the grid.
+    pub fn update_lambdas(&mut self, lambdas: (f64, f64)) -> Result<(), WhittakerError> {
+        validate_lambda(lambdas.0)?;
+        validate_lambda(lambdas.1)?;
+
+        self.lambdas = lambdas;
+
+        if let Solver2D::Sparse(_) = self.solver {
+            self.rebuild_solver()?;
+        }
+        Ok(())
+    }
+
+    /// Updates the orders of the smoother.
+    ///
+    /// # Arguments:
+    /// * `orders`: The order of the filter along the rows and columns of the grid.
+    pub fn update_orders(&mut self, orders: (usize, usize)) -> Result<(), WhittakerError> {
+        let row_penalty = axis_penalty(self.shape.0, orders.0, self.x_inputs.0.as_ref())?;
+        let column_penalty = axis_penalty(self.shape.1, orders.1, self.x_inputs.1.as_ref())?;
+
+        self.orders = orders;
+        self.row_penalty = row_penalty;
+        self.column_penalty = column_penalty;
+        self.rebuild_solver()?;
+        Ok(())
+    }
+
+    /// Updates the weights of the grid points.
+    ///
+    /// # Arguments:
+    /// * `weights`: The weight of each grid point in row-major order. Setting a weight to 0 results in interpolation.
+    pub fn update_weights(&mut self, weights: &[f64]) -> Result<(), WhittakerError> {
+        validate_weights(weights, self.shape.0 * self.shape.1)?;
+
+        self.weights = Some(weights.to_vec());
+        self.rebuild_solver()?;
+        Ok(())
+    }
+
+    /// Run two-dimensional Whittaker-Eilers smoothing and interpolation.
+    ///
+    /// # Arguments
+    /// * `y_input`: The grid of values to be smoothed and interpolated, in row-major order.
+    ///
+    /// # Returns:
+    /// The smoothed and interpolated grid in row-major order.
+    pub fn smooth(&self, y_input: &[f64]) -> Result<Vec<f64>, WhittakerError> {
+        let (rows, columns) = self.shape;
+
+        if y_input.len() != rows * columns {
+            return Err(WhittakerError::LengthMismatch(
+                rows * columns,
+                y_input.len(),
+            ));
+        }
+
+        match &self.solver {
+            Solver2D::Sparse(ldl) => match self.weights.as_ref() {
+                Some(weights) => Ok(ldl.solve(
+                    weights
+                        .iter()
+                        .zip(y_input)
+                        .map(|(a, b)| a * b)
+                        .collect::<Vec<f64>>(),
+                )),
+                None => Ok(ldl.solve(y_input)),
+            },
+            Solver2D::Array {
+                row_vectors,
+                row_values,
+                column_vectors,
+                column_values,
+            } => {
+                let y_mat = DMatrix::from_row_slice(rows, columns, y_input);
+
+                let mut rotated = row_vectors.transpose() * y_mat * column_vectors;
+
+                for column in 0..columns {
+                    for row in 0..rows {
+                        rotated[(row, column)] /= 1.0
+                            + self.lambdas.0 * row_values[row]
+                            + self.lambdas.1 * column_values[column];
+                    }
+                }
+
+                let smoothed = row_vectors * rotated * column_vectors.transpose();
+
+                Ok(smoothed.transpose().iter().copied().collect())
+            }
+        }
+    }
+
+    fn rebuild_solver(&mut self) -> Result<(), WhittakerError> {
+        self.solver = build_solver(
+            self.shape,
+            self.lambdas,
+            &self.row_penalty,
+            &self.column_penalty,
+            self.weights.as_ref(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Builds the solver for the grid. Without weights and with short enough axes, this is the eigendecomposition of each axis' penalty.
+fn build_solver(
+    shape: (usize, usize),
+    lambdas: (f64, f64),
+    row_penalty: &CsMat<f64>,
+    column_penalty: &CsMat<f64>,
+    weights: Option<&Vec<f64>>,
+) -> Result<Solver2D, WhittakerError> {
+    let (rows, columns) = shape;
+    let use_array = weights.is_none() && rows.max(columns) <= ARRAY_MAX_AXIS_LENGTH;
+
+    if use_array {
+        let rows = row_penalty.to_dense();
+        let columns = column_penalty.to_dense();
+
+        let rows =
+            DMatrix::from_row_iterator(shape.0, shape.0, rows.iter().copied()).symmetric_eigen();
+        let columns =
+            DMatrix::from_row_iterator(shape.1, shape.1, columns.iter().copied()).symmetric_eigen();
+
+        return Ok(Solver2D::Array {
+            row_vectors: rows.eigenvectors,
+            row_values: rows.eigenvalues,
+            column_vectors: columns.eigenvectors,
+            column_values: columns.eigenvalues,
+        });
+    }
+
+    let weights = weights
+        .cloned()
+        .unwrap_or_else(|| vec![1.0; rows * columns]);
+
+    let penalty = &(&kronecker_product::<f64, f64, usize, usize>(
+        row_penalty.view(),
+        CsMat::<f64>::eye(columns).view(),
+    ) * lambdas.0)
+        + &(&kronecker_product::<f64, f64, usize, usize>(
+            CsMat::<f64>::eye(rows).view(),
+            column_penalty.view(),
+        ) * lambdas.1);
+
+    let to_solve = &CsMat::new_csc(
+        (weights.len(), weights.len()),
+        (0..weights.len() + 1).collect(),
+        (0..weights.len()).collect(),
+        weights,
+    ) + &penalty;
+
+    let ldl = Ldl::new()
+        .fill_in_reduction(ReverseCuthillMcKee)
+        .check_symmetry(CheckSymmetry)
+        .numeric(to_solve.view())
+        .map_err(WhittakerError::SolverError)?;
+
+    Ok(Solver2D::Sparse(ldl))
+}
+
+/// Builds the penalty D'D along a single axis of the grid.
+fn axis_penalty(
+    length: usize,
+    order: usize,
+    x_input: Option<&Vec<f64>>,
+) -> Result<CsMat<f64>, WhittakerError> {
+    if length < order {
+        return Err(WhittakerError::DataTooShort(length, order));
+    }
+    let d_mat = match x_input {
+        Some(x) => {
+            validate_x_input(x, length)?;
+            ddmat(x, length, order)
+        }
+        None => diff_no_ddmat(&CsMat::eye(length), order),
+    };
+
+    Ok((&d_mat.transpose_view() * &d_mat).to_csr())
+}
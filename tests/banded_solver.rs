@@ -0,0 +1,88 @@
+use crate::common::noisy_sine;
+use approx::assert_relative_eq;
+use nalgebra::{DMatrix, DVector};
+use whittaker_eilers::WhittakerSmoother;
+
+/// Builds the divided difference matrix of the given order with dense matrices.
+fn dense_difference(x: &[f64], order: usize) -> DMatrix<f64> {
+    let n = x.len();
+    let mut d = DMatrix::<f64>::identity(n, n);
+    for k in 1..=order {
+        let rows = n - k;
+        let mut next = DMatrix::<f64>::zeros(rows, n);
+        for row in 0..rows {
+            let scale = 1.0 / (x[row + k] - x[row]);
+            for column in 0..n {
+                next[(row, column)] = scale * (d[(row + 1, column)] - d[(row, column)]);
+            }
+        }
+        d = next;
+    }
+    d
+}
+
+#[test]
+fn banded_solver_matches_dense_solution() {
+    let y = noisy_sine(60);
+    let n = y.len();
+    let x = (0..n)
+        .map(|i| i as f64 + 0.3 * (i as f64 * 1.7).sin())
+        .collect::<Vec<f64>>();
+    let mut weights = (0..n)
+        .map(|i| 0.5 + ((i * 31) % 7) as f64 / 7.0)
+        .collect::<Vec<f64>>();
+    weights[10] = 0.0;
+    weights[11] = 0.0;
+    weights[40] = 0.0;
+
+    for order in 0..=5 {
+        let lambda = 10.0;
+        let smoother = WhittakerSmoother::new(lambda, order, n, Some(&x), Some(&weights)).unwrap();
+
+        let d = dense_difference(&x, order);
+        let w = DMatrix::from_diagonal(&DVector::from_vec(weights.clone()));
+        let inverse = (&w + lambda * d.transpose() * &d).try_inverse().unwrap();
+        let expected = &inverse * &w * DVector::from_vec(y.clone());
+
+        let result = smoother.smooth_and_cross_validate(&y).unwrap();
+        for (actual, expected) in result.smoothed.iter().zip(expected.iter()) {
+            assert_relative_eq!(*actual, *expected, epsilon = 1e-8);
+        }
+
+        let edf = (&inverse * &w).trace();
+        assert_relative_eq!(result.effective_degrees_of_freedom, edf, epsilon = 1e-8);
+    }
+}
+
+#[test]
+fn banded_solver_handles_update_of_lambda_and_weights() {
+    let y = noisy_sine(200);
+    let n = y.len();
+    let mut smoother = WhittakerSmoother::new(1.0, 3, n, None, None).unwrap();
+
+    let mut weights = vec![1.0; n];
+    for weight in weights.iter_mut().skip(50).take(20) {
+        *weight = 0.0;
+    }
+    smoother.update_weights(&weights).unwrap();
+    smoother.update_lambda(1e4).unwrap();
+
+    let fresh = WhittakerSmoother::new(1e4, 3, n, None, Some(&weights)).unwrap();
+
+    assert_eq!(smoother.smooth(&y).unwrap(), fresh.smooth(&y).unwrap());
+}
+
+#[test]
+fn banded_solver_handles_short_series() {
+    // With the data length equal to order + 1, the system is dense.
+    let y = vec![1.0, 3.0, 2.0, 5.0];
+    let smoother = WhittakerSmoother::new(1e2, 3, y.len(), None, None).unwrap();
+
+    let d = DMatrix::<f64>::from_row_slice(1, 4, &[-1.0, 3.0, -3.0, 1.0]);
+    let system = DMatrix::<f64>::identity(4, 4) + d.transpose() * &d * 1e2;
+    let expected = system.lu().solve(&DVector::from_vec(y.clone())).unwrap();
+
+    for (actual, expected) in smoother.smooth(&y).unwrap().iter().zip(expected.iter()) {
+        assert_relative_eq!(*actual, *expected, epsilon = 1e-10);
+    }
+}
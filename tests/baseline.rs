@@ -0,0 +1,80 @@
+use whittaker_eilers::{BaselineMethod, WhittakerError, WhittakerSmoother};
+
+fn peaks_on_slope() -> (Vec<f64>, Vec<f64>) {
+    let x = (0..500).map(|i| i as f64).collect::<Vec<f64>>();
+    let true_baseline = x
+        .iter()
+        .map(|x| 5.0 + 0.01 * x + 2.0 * (x / 150.0).sin())
+        .collect::<Vec<f64>>();
+    let signal = x
+        .iter()
+        .zip(&true_baseline)
+        .map(|(x, b)| {
+            b + 20.0 * (-(x - 100.0).powi(2) / 50.0).exp()
+                + 15.0 * (-(x - 250.0).powi(2) / 80.0).exp()
+                + 25.0 * (-(x - 400.0).powi(2) / 30.0).exp()
+        })
+        .collect::<Vec<f64>>();
+    (true_baseline, signal)
+}
+
+#[test]
+fn baseline_methods_recover_baseline() {
+    let (true_baseline, signal) = peaks_on_slope();
+
+    let mut smoother = WhittakerSmoother::new(1e6, 2, signal.len(), None, None).unwrap();
+
+    for method in [
+        BaselineMethod::Als(0.001),
+        BaselineMethod::ArPls,
+        BaselineMethod::AirPls,
+    ] {
+        let result = smoother.baseline(&signal, method, 100, 1e-3).unwrap();
+
+        assert!(result.converged);
+        assert!(result.iterations > 0);
+
+        let max_error = result
+            .baseline
+            .iter()
+            .zip(&true_baseline)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        assert!(max_error < 1.0, "{:?}: {}", method, max_error);
+
+        for ((corrected, y), z) in result.corrected.iter().zip(&signal).zip(&result.baseline) {
+            assert_eq!(*corrected, y - z);
+        }
+    }
+}
+
+#[test]
+fn baseline_restores_weights() {
+    let (_, signal) = peaks_on_slope();
+
+    let mut smoother = WhittakerSmoother::new(1e6, 2, signal.len(), None, None).unwrap();
+
+    let before = smoother.smooth(&signal).unwrap();
+    smoother
+        .baseline(&signal, BaselineMethod::Als(0.01), 10, 1e-3)
+        .unwrap();
+    let after = smoother.smooth(&signal).unwrap();
+
+    assert_eq!(before, after);
+}
+
+#[test]
+fn baseline_invalid_asymmetry() {
+    let (_, signal) = peaks_on_slope();
+
+    let mut smoother = WhittakerSmoother::new(1e6, 2, signal.len(), None, None).unwrap();
+
+    for p in [0.0, 1.0, -0.5, f64::NAN] {
+        assert_eq!(
+            smoother
+                .baseline(&signal, BaselineMethod::Als(p), 10, 1e-3)
+                .unwrap_err(),
+            WhittakerError::InvalidParameter("p")
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{Boundary, WhittakerError, WhittakerSmoother};
+
+/// A stack of pixel time series, each with its own cloud mask as weights.
+fn pixels() -> (Vec<f64>, Vec<Vec<f64>>, Vec<Vec<f64>>) {
+    let x = (0..80)
+        .map(|i| 5.0 * i as f64 + (i as f64 * 0.9).sin())
+        .collect::<Vec<f64>>();
+    let y_inputs = (0..6)
+        .map(|pixel| {
+            x.iter()
+                .enumerate()
+                .map(|(i, x)| (x / 60.0 + pixel as f64).sin() + 0.1 * ((i * 31 + pixel) % 7) as f64)
+                .collect::<Vec<f64>>()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    let weights = (0..6)
+        .map(|pixel| {
+            (0..x.len())
+                .map(|i| if (i + 3 * pixel) % 5 == 0 { 0.0 } else { 1.0 })
+                .collect::<Vec<f64>>()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    (x, y_inputs, weights)
+}
+
+#[test]
+fn batch_matches_individual_smoothers() {
+    let (x, y_inputs, weights) = pixels();
+
+    for boundary in [
+        Boundary::Open,
+        Boundary::Periodic {
+            period: Some(410.0),
+        },
+    ] {
+        let mut smoother = WhittakerSmoother::new(1e3, 2, x.len(), Some(&x), None).unwrap();
+        smoother.update_boundary(boundary).unwrap();
+
+        let results = smoother.smooth_batch(&y_inputs, &weights).unwrap();
+        assert_eq!(results.len(), y_inputs.len());
+
+        for ((result, y_input), weights) in results.iter().zip(&y_inputs).zip(&weights) {
+            let mut individual =
+                WhittakerSmoother::new(1e3, 2, x.len(), Some(&x), Some(weights)).unwrap();
+            individual.update_boundary(boundary).unwrap();
+
+            for (a, b) in result
+                .as_ref()
+                .unwrap()
+                .iter()
+                .zip(&individual.smooth(y_input).unwrap())
+            {
+                assert_relative_eq!(*a, *b, epsilon = 1e-10);
+            }
+        }
+    }
+}
+
+#[test]
+fn batch_errors() {
+    let (x, mut y_inputs, mut weights) = pixels();
+    let smoother = WhittakerSmoother::new(1e3, 2, x.len(), Some(&x), None).unwrap();
+
+    assert_eq!(
+        smoother.smooth_batch(&y_inputs, &weights[1..]).unwrap_err(),
+        WhittakerError::LengthMismatch(6, 5)
+    );
+
+    // A fully clouded pixel or a short series fails on its own, without affecting the rest.
+    weights[2] = vec![0.0; x.len()];
+    y_inputs[4].pop();
+    let results = smoother.smooth_batch(&y_inputs, &weights).unwrap();
+
+    assert_eq!(results[2], Err(WhittakerError::AllWeightsZero));
+    assert_eq!(
+        results[4],
+        Err(WhittakerError::LengthMismatch(x.len(), x.len() - 1))
+    );
+    assert!(results
+        .iter()
+        .enumerate()
+        .all(|(i, result)| result.is_ok() == (i != 2 && i != 4)));
+}
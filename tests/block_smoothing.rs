@@ -0,0 +1,131 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{BlockLayout, Boundary, WhittakerError, WhittakerSmoother};
+
+/// Enough series to be solved in more than one tile.
+const SERIES: usize = 1000;
+
+fn series(j: usize, length: usize) -> Vec<f64> {
+    (0..length)
+        .map(|i| (i as f64 / (5.0 + j as f64)).sin() + 0.2 * ((i * 13 + j * 7) % 11) as f64 / 11.0)
+        .collect()
+}
+
+/// Smooths a padded block in both layouts and checks each series against `smooth`.
+fn check_block(smoother: &WhittakerSmoother) {
+    let length = smoother.get_data_length();
+    let all_series = (0..SERIES)
+        .map(|j| series(j, length))
+        .collect::<Vec<Vec<f64>>>();
+
+    for layout in [
+        BlockLayout::RowMajor { stride: SERIES + 2 },
+        BlockLayout::ColumnMajor { stride: length + 3 },
+    ] {
+        let (index, size): (Box<dyn Fn(usize, usize) -> usize>, usize) = match layout {
+            BlockLayout::RowMajor { stride } => {
+                (Box::new(move |i, j| i * stride + j), length * stride)
+            }
+            BlockLayout::ColumnMajor { stride } => {
+                (Box::new(move |i, j| j * stride + i), SERIES * stride)
+            }
+        };
+
+        let mut y_block = vec![f64::NAN; size];
+        for (j, y) in all_series.iter().enumerate() {
+            for (i, value) in y.iter().enumerate() {
+                y_block[index(i, j)] = *value;
+            }
+        }
+
+        let mut output = vec![-1.0; size];
+        smoother
+            .smooth_block(&y_block, SERIES, layout, &mut output)
+            .unwrap();
+
+        for (j, y) in all_series.iter().enumerate() {
+            for (i, expected) in smoother.smooth(y).unwrap().iter().enumerate() {
+                assert_relative_eq!(output[index(i, j)], *expected, epsilon = 1e-10);
+            }
+        }
+
+        // The padding is neither read nor written.
+        assert_eq!(
+            output.iter().filter(|value| **value == -1.0).count(),
+            size - length * SERIES
+        );
+    }
+}
+
+#[test]
+fn block_matches_smooth() {
+    let x = (0..60)
+        .map(|i| i as f64 + 0.4 * (i as f64).sin())
+        .collect::<Vec<f64>>();
+    let weights = (0..60)
+        .map(|i| if i % 9 == 4 { 0.0 } else { 1.0 })
+        .collect::<Vec<f64>>();
+
+    check_block(&WhittakerSmoother::new(1e2, 2, 60, None, None).unwrap());
+    check_block(&WhittakerSmoother::new(1e3, 3, 60, Some(&x), Some(&weights)).unwrap());
+
+    // Periodic boundaries need the sparse factorisation and its permutation.
+    let mut periodic = WhittakerSmoother::new(1e2, 2, 60, None, Some(&weights)).unwrap();
+    periodic
+        .update_boundary(Boundary::Periodic { period: None })
+        .unwrap();
+    check_block(&periodic);
+}
+
+#[test]
+fn invalid_blocks() {
+    let smoother = WhittakerSmoother::new(1e2, 2, 10, None, None).unwrap();
+    let mut y_block = (0..30).map(|i| i as f64).collect::<Vec<f64>>();
+    let mut output = vec![0.0; 30];
+
+    assert_eq!(
+        smoother
+            .smooth_block(
+                &y_block,
+                3,
+                BlockLayout::RowMajor { stride: 2 },
+                &mut output
+            )
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("stride")
+    );
+    assert_eq!(
+        smoother
+            .smooth_block(
+                &y_block,
+                3,
+                BlockLayout::ColumnMajor { stride: 11 },
+                &mut output
+            )
+            .unwrap_err(),
+        WhittakerError::LengthMismatch(32, 30)
+    );
+    assert_eq!(
+        smoother
+            .smooth_block(
+                &y_block,
+                3,
+                BlockLayout::RowMajor { stride: 3 },
+                &mut output[1..]
+            )
+            .unwrap_err(),
+        WhittakerError::LengthMismatch(30, 29)
+    );
+
+    y_block[17] = f64::NAN;
+    assert_eq!(
+        smoother
+            .smooth_block(
+                &y_block,
+                3,
+                BlockLayout::ColumnMajor { stride: 10 },
+                &mut output
+            )
+            .unwrap_err(),
+        WhittakerError::InvalidValue("y_block", 17)
+    );
+}
@@ -0,0 +1,152 @@
+use whittaker_eilers::{WhittakerError, WhittakerSmoother};
+
+fn peaks(length: usize) -> Vec<f64> {
+    (0..length)
+        .map(|i| {
+            let x = i as f64;
+            5.0 * (-(x - 20.0).powi(2) / 8.0).exp() + 3.0 * (-(x - 45.0).powi(2) / 4.0).exp()
+        })
+        .collect()
+}
+
+/// Computes `(W + λD'D) z - W y` for a second order smoother, the gradient of its objective divided by 2.
+fn gradient(y: &[f64], z: &[f64], weights: &[f64], lambda: f64) -> Vec<f64> {
+    let n = y.len();
+    let second_differences = (0..n - 2)
+        .map(|i| z[i] - 2.0 * z[i + 1] + z[i + 2])
+        .collect::<Vec<f64>>();
+    let mut gradient = (0..n)
+        .map(|i| weights[i] * (z[i] - y[i]))
+        .collect::<Vec<f64>>();
+    for (i, difference) in second_differences.iter().enumerate() {
+        gradient[i] += lambda * difference;
+        gradient[i + 1] -= 2.0 * lambda * difference;
+        gradient[i + 2] += lambda * difference;
+    }
+    gradient
+}
+
+#[test]
+fn lower_bound_keeps_interpolated_gap_non_negative() {
+    let y = peaks(60);
+    let mut weights = vec![1.0; y.len()];
+    for weight in weights.iter_mut().skip(26).take(12) {
+        *weight = 0.0;
+    }
+    let lambda = 2.0;
+    let mut smoother = WhittakerSmoother::new(lambda, 2, y.len(), None, Some(&weights)).unwrap();
+
+    let unconstrained = smoother.smooth(&y).unwrap();
+    assert!(unconstrained.iter().any(|z| *z < -0.1));
+
+    let lower = vec![0.0; y.len()];
+    let result = smoother.smooth_bounded(&y, Some(&lower), None, 50).unwrap();
+
+    assert!(result.converged);
+    assert!(result.iterations > 0);
+    assert!(result.smoothed.iter().all(|z| *z >= 0.0));
+    assert!(result.on_lower_bound.iter().any(|on_bound| *on_bound));
+    assert!(result.on_upper_bound.iter().all(|on_bound| !on_bound));
+
+    // The Karush-Kuhn-Tucker conditions: the gradient vanishes away from the bound and pushes against it on the bound.
+    let gradient = gradient(&y, &result.smoothed, &weights, lambda);
+    for (i, gradient) in gradient.iter().enumerate() {
+        if result.on_lower_bound[i] {
+            assert_eq!(result.smoothed[i], 0.0);
+            assert!(*gradient > -1e-6);
+        } else {
+            assert!(gradient.abs() < 1e-6);
+        }
+    }
+
+    // The smoother's own weights are restored.
+    assert_eq!(smoother.smooth(&y).unwrap(), unconstrained);
+}
+
+#[test]
+fn upper_and_lower_bounds_per_point() {
+    let y = (0..80)
+        .map(|i| (i as f64 / 6.0).sin() * 2.0)
+        .collect::<Vec<f64>>();
+    let mut smoother = WhittakerSmoother::new(5.0, 2, y.len(), None, None).unwrap();
+
+    let lower = (0..y.len())
+        .map(|i| if i < 40 { -1.0 } else { f64::NEG_INFINITY })
+        .collect::<Vec<f64>>();
+    let upper = vec![1.5; y.len()];
+    let result = smoother
+        .smooth_bounded(&y, Some(&lower), Some(&upper), 50)
+        .unwrap();
+
+    assert!(result.converged);
+    for i in 0..y.len() {
+        assert!(result.smoothed[i] >= lower[i]);
+        assert!(result.smoothed[i] <= upper[i]);
+        assert!(!(result.on_lower_bound[i] && result.on_upper_bound[i]));
+        assert!(!(i >= 40 && result.on_lower_bound[i]));
+    }
+    assert!(result.on_lower_bound.iter().any(|on_bound| *on_bound));
+    assert!(result.on_upper_bound.iter().any(|on_bound| *on_bound));
+    assert!(result.smoothed[40..].iter().any(|z| *z < -1.5));
+}
+
+#[test]
+fn inactive_bounds_do_not_change_smoothing() {
+    let y = peaks(50);
+    let mut smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+
+    let result = smoother
+        .smooth_bounded(
+            &y,
+            Some(&vec![-10.0; y.len()]),
+            Some(&vec![10.0; y.len()]),
+            10,
+        )
+        .unwrap();
+
+    assert!(result.converged);
+    assert_eq!(result.iterations, 0);
+    assert_eq!(result.smoothed, smoother.smooth(&y).unwrap());
+}
+
+#[test]
+fn bounded_smoothing_in_single_precision() {
+    let y = peaks(60).iter().map(|y| *y as f32).collect::<Vec<f32>>();
+    let mut weights = vec![1.0_f32; y.len()];
+    for weight in weights.iter_mut().skip(26).take(12) {
+        *weight = 0.0;
+    }
+    let mut smoother = WhittakerSmoother::new(2.0_f32, 2, y.len(), None, Some(&weights)).unwrap();
+
+    let result = smoother
+        .smooth_bounded(&y, Some(&vec![0.0; y.len()]), None, 50)
+        .unwrap();
+
+    assert!(result.converged);
+    assert!(result.smoothed.iter().all(|z| *z >= 0.0));
+}
+
+#[test]
+fn bounded_smoothing_rejects_invalid_bounds() {
+    let y = peaks(20);
+    let mut smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+
+    assert_eq!(
+        smoother
+            .smooth_bounded(&y, Some(&[0.0; 19]), None, 10)
+            .unwrap_err(),
+        WhittakerError::LengthMismatch(20, 19)
+    );
+    assert_eq!(
+        smoother
+            .smooth_bounded(&y, Some(&[1.0; 20]), Some(&[0.0; 20]), 10)
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("bounds")
+    );
+    assert_eq!(
+        smoother
+            .smooth_bounded(&y, None, Some(&[f64::NAN; 20]), 10)
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("bounds")
+    );
+}
@@ -0,0 +1,24 @@
+//! Data generators shared by the tests.
+
+/// A sine wave with deterministic, roughly uniform noise.
+pub fn noisy_sine(length: usize) -> Vec<f64> {
+    (0..length)
+        .map(|i| (i as f64 / 10.0).sin() + 0.4 * ((i * 7919) % 23) as f64 / 23.0 - 0.2)
+        .collect()
+}
+
+/// A sine wave with strongly autocorrelated AR(1) noise, generated from the given seed.
+pub fn autocorrelated_sine(length: usize, seed: u64) -> Vec<f64> {
+    let mut state = seed;
+    let mut noise = 0.0;
+    (0..length)
+        .map(|i| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let uniform = (state >> 11) as f64 / (1_u64 << 53) as f64 - 0.5;
+            noise = 0.9 * noise + 0.1 * uniform;
+            (i as f64 / 40.0).sin() + noise
+        })
+        .collect()
+}
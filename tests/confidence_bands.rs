@@ -0,0 +1,93 @@
+use approx::assert_relative_eq;
+use nalgebra::DMatrix;
+use whittaker_eilers::{WhittakerError, WhittakerSmoother};
+
+fn noisy_sine(length: usize) -> Vec<f64> {
+    (0..length)
+        .map(|i| (i as f64 / 10.0).sin() + 0.2 * ((i * 7919) % 23) as f64 / 23.0 - 0.1)
+        .collect()
+}
+
+#[test]
+fn bands_match_dense_computation() {
+    let y = noisy_sine(60);
+    let mut weights = vec![1.0; y.len()];
+    weights[30] = 0.0;
+    weights[31] = 0.5;
+    let lambda = 50.0;
+
+    let smoother = WhittakerSmoother::new(lambda, 2, y.len(), None, Some(&weights)).unwrap();
+    let result = smoother.smooth_with_confidence_bands(&y, 0.95).unwrap();
+
+    let n = y.len();
+    let d = DMatrix::from_fn(n - 2, n, |row, column| {
+        match column as isize - row as isize {
+            0 | 2 => 1.0,
+            1 => -2.0,
+            _ => 0.0,
+        }
+    });
+    let inverse = (DMatrix::from_diagonal(&nalgebra::DVector::from_vec(weights.clone()))
+        + lambda * d.transpose() * d)
+        .try_inverse()
+        .unwrap();
+
+    let edf = (0..n).map(|i| inverse[(i, i)] * weights[i]).sum::<f64>();
+    assert_relative_eq!(result.effective_degrees_of_freedom, edf, epsilon = 1e-8);
+
+    let residual_variance = (0..n)
+        .map(|i| weights[i] * (y[i] - result.smoothed[i]).powi(2))
+        .sum::<f64>()
+        / (n as f64 - 1.0 - edf);
+    assert_relative_eq!(result.residual_variance, residual_variance, epsilon = 1e-10);
+
+    for i in 0..n {
+        let standard_error = (residual_variance * inverse[(i, i)]).sqrt();
+        assert_relative_eq!(result.standard_errors[i], standard_error, epsilon = 1e-10);
+        assert_relative_eq!(
+            result.upper[i],
+            result.smoothed[i] + 1.959964 * standard_error,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            result.lower[i],
+            result.smoothed[i] - 1.959964 * standard_error,
+            epsilon = 1e-6
+        );
+    }
+
+    assert!(result.standard_errors[30] > result.standard_errors[25]);
+}
+
+#[test]
+fn wider_bands_at_higher_confidence() {
+    let y = noisy_sine(500);
+
+    let smoother = WhittakerSmoother::new(1e3, 2, y.len(), None, None).unwrap();
+    let narrow = smoother.smooth_with_confidence_bands(&y, 0.9).unwrap();
+    let wide = smoother.smooth_with_confidence_bands(&y, 0.99).unwrap();
+
+    for i in 0..y.len() {
+        assert_relative_eq!(
+            (wide.upper[i] - wide.lower[i]) / (narrow.upper[i] - narrow.lower[i]),
+            2.575829 / 1.644854,
+            epsilon = 1e-5
+        );
+    }
+}
+
+#[test]
+fn invalid_confidence_level() {
+    let y = noisy_sine(20);
+
+    let smoother = WhittakerSmoother::new(1e3, 2, y.len(), None, None).unwrap();
+
+    for level in [0.0, 1.0, 1.5, f64::NAN] {
+        assert_eq!(
+            smoother
+                .smooth_with_confidence_bands(&y, level)
+                .unwrap_err(),
+            WhittakerError::InvalidParameter("confidence_level")
+        );
+    }
+}
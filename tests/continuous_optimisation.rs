@@ -0,0 +1,89 @@
+use crate::common::noisy_sine;
+use whittaker_eilers::{SelectionCriterion, WhittakerError, WhittakerSmoother};
+
+#[test]
+fn continuous_search_beats_the_grid() {
+    let y = noisy_sine(300);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    for criterion in [
+        SelectionCriterion::CrossValidation,
+        SelectionCriterion::GeneralizedCrossValidation,
+        SelectionCriterion::Reml,
+    ] {
+        let grid = smoother
+            .smooth_optimal_with_criterion(&y, false, criterion)
+            .unwrap()
+            .get_optimal();
+
+        let continuous = smoother
+            .smooth_optimal_continuous(&y, false, criterion, (1e-5, 1e8), 0.01, 50)
+            .unwrap();
+        let optimal = continuous.get_optimal();
+
+        assert!(continuous.validation_results.len() < 27);
+        assert!(optimal.score <= grid.score);
+        assert!((optimal.lambda.log10() - grid.lambda.log10()).abs() <= 0.25);
+    }
+}
+
+#[test]
+fn continuous_search_respects_limits() {
+    let y = noisy_sine(100);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let results = smoother
+        .smooth_optimal_continuous(
+            &y,
+            true,
+            SelectionCriterion::CrossValidation,
+            (1e1, 1e4),
+            1e-6,
+            8,
+        )
+        .unwrap();
+
+    assert_eq!(results.validation_results.len(), 8);
+    for result in &results.validation_results {
+        assert!(result.lambda >= 1e1 && result.lambda <= 1e4);
+        assert_eq!(result.smoothed.len(), y.len());
+    }
+
+    let optimal = results.get_optimal();
+    assert!(results
+        .validation_results
+        .iter()
+        .all(|result| result.score >= optimal.score));
+}
+
+#[test]
+fn continuous_search_rejects_invalid_parameters() {
+    let y = noisy_sine(50);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+    let criterion = SelectionCriterion::CrossValidation;
+
+    assert_eq!(
+        smoother
+            .smooth_optimal_continuous(&y, false, criterion, (0.0, 1e4), 0.01, 50)
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("lambda_bounds")
+    );
+    assert_eq!(
+        smoother
+            .smooth_optimal_continuous(&y, false, criterion, (1e4, 1e2), 0.01, 50)
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("lambda_bounds")
+    );
+    assert_eq!(
+        smoother
+            .smooth_optimal_continuous(&y, false, criterion, (1e2, 1e4), 0.0, 50)
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("tolerance")
+    );
+    assert_eq!(
+        smoother
+            .smooth_optimal_continuous(&y, false, criterion, (1e2, 1e4), 0.01, 0)
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("max_evaluations")
+    );
+}
@@ -0,0 +1,118 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{Boundary, WhittakerError, WhittakerSmoother};
+
+#[test]
+fn derivatives_of_quadratic_are_exact() {
+    // A quadratic is not penalised by a third order smoother, so is reproduced exactly.
+    let y = (0..40)
+        .map(|i| 0.5 * (i as f64).powi(2) - 3.0 * i as f64 + 2.0)
+        .collect::<Vec<f64>>();
+    let smoother = WhittakerSmoother::new(1e3, 3, y.len(), None, None).unwrap();
+
+    let velocity = smoother.smooth_derivative(&y, 1).unwrap();
+    let acceleration = smoother.smooth_derivative(&y, 2).unwrap();
+    let jerk = smoother.smooth_derivative(&y, 3).unwrap();
+
+    assert_eq!(velocity.len(), y.len());
+    for i in 0..y.len() {
+        assert_relative_eq!(velocity[i], i as f64 - 3.0, epsilon = 1e-7);
+        assert_relative_eq!(acceleration[i], 1.0, epsilon = 1e-7);
+        assert_relative_eq!(jerk[i], 0.0, epsilon = 1e-7);
+    }
+}
+
+#[test]
+fn derivatives_use_unequal_spacing() {
+    let x = (0..400)
+        .map(|i| i as f64 * 0.025 + 0.008 * (i as f64 * 1.3).sin())
+        .collect::<Vec<f64>>();
+    let y = x.iter().map(|x| x.sin()).collect::<Vec<f64>>();
+    let smoother = WhittakerSmoother::new(1e-12, 2, y.len(), Some(&x), None).unwrap();
+
+    let velocity = smoother.smooth_derivative(&y, 1).unwrap();
+    let acceleration = smoother.smooth_derivative(&y, 2).unwrap();
+
+    for i in 0..x.len() {
+        assert_relative_eq!(velocity[i], x[i].cos(), epsilon = 1e-3);
+        assert_relative_eq!(acceleration[i], -x[i].sin(), epsilon = 1e-3);
+    }
+}
+
+#[test]
+fn derivative_of_order_zero_is_smoothed_data() {
+    let y = (0..30)
+        .map(|i| (i as f64 / 4.0).sin() + ((i * 13) % 5) as f64 * 0.1)
+        .collect::<Vec<f64>>();
+    let smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+
+    assert_eq!(
+        smoother.smooth_derivative(&y, 0).unwrap(),
+        smoother.smooth(&y).unwrap()
+    );
+}
+
+#[test]
+fn derivative_of_short_series() {
+    let y = vec![1.0, 4.0, 2.0];
+    let smoother = WhittakerSmoother::new(1e2, 1, y.len(), None, None).unwrap();
+
+    // A single second difference is spread across every sample.
+    let smoothed = smoother.smooth(&y).unwrap();
+    let curvature = smoothed[0] - 2.0 * smoothed[1] + smoothed[2];
+    assert_eq!(
+        smoother.smooth_derivative(&y, 2).unwrap(),
+        vec![curvature; 3]
+    );
+
+    assert_eq!(
+        smoother.smooth_derivative(&y, 3).unwrap_err(),
+        WhittakerError::DataTooShort(3, 3)
+    );
+}
+
+#[test]
+fn periodic_derivatives_wrap_around() {
+    let y = (0..60)
+        .map(|i| (i as f64 / 60.0 * std::f64::consts::TAU).sin() + ((i * 13) % 5) as f64 * 0.1)
+        .collect::<Vec<f64>>();
+    let mut smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+    smoother
+        .update_boundary(Boundary::Periodic { period: None })
+        .unwrap();
+
+    // With no ends, rotating the data rotates its derivatives.
+    let mut rotated = y.clone();
+    rotated.rotate_left(17);
+    for derivative_order in 1..=3 {
+        let mut expected = smoother.smooth_derivative(&y, derivative_order).unwrap();
+        expected.rotate_left(17);
+        let derivative = smoother
+            .smooth_derivative(&rotated, derivative_order)
+            .unwrap();
+        for (a, b) in derivative.iter().zip(&expected) {
+            assert_relative_eq!(*a, *b, epsilon = 1e-10);
+        }
+    }
+}
+
+#[test]
+fn periodic_derivatives_use_unequal_spacing() {
+    let x = (0..400)
+        .map(|i| i as f64 * 0.015 + 0.005 * (i as f64 * 1.3).sin())
+        .collect::<Vec<f64>>();
+    let y = x.iter().map(|x| x.sin()).collect::<Vec<f64>>();
+    let mut smoother = WhittakerSmoother::new(1e-12, 2, y.len(), Some(&x), None).unwrap();
+    smoother
+        .update_boundary(Boundary::Periodic {
+            period: Some(std::f64::consts::TAU),
+        })
+        .unwrap();
+
+    let velocity = smoother.smooth_derivative(&y, 1).unwrap();
+    let acceleration = smoother.smooth_derivative(&y, 2).unwrap();
+
+    for i in 0..x.len() {
+        assert_relative_eq!(velocity[i], x[i].cos(), epsilon = 1e-3);
+        assert_relative_eq!(acceleration[i], -x[i].sin(), epsilon = 1e-3);
+    }
+}
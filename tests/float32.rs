@@ -0,0 +1,109 @@
+use crate::common::noisy_sine;
+use approx::assert_relative_eq;
+use whittaker_eilers::{
+    CrossValidationResult, LambdaSearch, OptimisedSmoothResult, RobustLoss, SelectionCriterion,
+    SmoothOptimalOptions, WhittakerSmoother,
+};
+
+fn to_f32(values: &[f64]) -> Vec<f32> {
+    values.iter().map(|v| *v as f32).collect()
+}
+
+#[test]
+fn f32_smoothing_matches_f64() {
+    let y = noisy_sine(200);
+    let x = (0..y.len()).map(|i| i as f64 * 0.5).collect::<Vec<f64>>();
+    let mut weights = vec![1.0; y.len()];
+    weights[50] = 0.0;
+    weights[51] = 0.5;
+
+    let smoother_64 = WhittakerSmoother::new(5e2, 2, y.len(), Some(&x), Some(&weights)).unwrap();
+    let smoother_32 = WhittakerSmoother::new(
+        5e2_f32,
+        2,
+        y.len(),
+        Some(&to_f32(&x)),
+        Some(&to_f32(&weights)),
+    )
+    .unwrap();
+
+    let smoothed_64 = smoother_64.smooth(&y).unwrap();
+    let smoothed_32 = smoother_32.smooth(&to_f32(&y)).unwrap();
+
+    for (a, b) in smoothed_64.iter().zip(&smoothed_32) {
+        assert_relative_eq!(*a, *b as f64, epsilon = 1e-4);
+    }
+}
+
+#[test]
+fn f32_cross_validation_matches_f64() {
+    let y = noisy_sine(150);
+
+    let smoother_64 = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+    let smoother_32 = WhittakerSmoother::new(1e2_f32, 2, y.len(), None, None).unwrap();
+
+    let result_64 = smoother_64
+        .smooth_and_score(&y, SelectionCriterion::GeneralizedCrossValidation)
+        .unwrap();
+    let result_32: CrossValidationResult<f32> = smoother_32
+        .smooth_and_score(&to_f32(&y), SelectionCriterion::GeneralizedCrossValidation)
+        .unwrap();
+
+    assert_relative_eq!(
+        result_64.cross_validation_error,
+        result_32.cross_validation_error as f64,
+        max_relative = 1e-4
+    );
+    assert_relative_eq!(
+        result_64.effective_degrees_of_freedom,
+        result_32.effective_degrees_of_freedom as f64,
+        max_relative = 1e-4
+    );
+    assert_relative_eq!(result_64.score, result_32.score as f64, max_relative = 1e-4);
+}
+
+#[test]
+fn f32_smooth_optimal_selects_same_lambda_as_f64() {
+    let y = noisy_sine(300);
+    let options = SmoothOptimalOptions {
+        lambdas: LambdaSearch::Range {
+            min: 1e-2,
+            max: 1e5,
+            steps: 15,
+        },
+        ..Default::default()
+    };
+
+    let mut smoother_64 = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+    let mut smoother_32 = WhittakerSmoother::new(1.0_f32, 2, y.len(), None, None).unwrap();
+
+    let optimal_64 = smoother_64
+        .smooth_optimal_with_options(&y, &options)
+        .unwrap()
+        .get_optimal();
+    let result_32: OptimisedSmoothResult<f32> = smoother_32
+        .smooth_optimal_with_options(&to_f32(&y), &options)
+        .unwrap();
+    let optimal_32 = result_32.get_optimal();
+
+    assert_relative_eq!(
+        optimal_64.lambda,
+        optimal_32.lambda as f64,
+        max_relative = 1e-4
+    );
+    assert_eq!(optimal_32.smoothed.len(), y.len());
+}
+
+#[test]
+fn f32_robust_smoothing_downweights_outlier() {
+    let mut y = to_f32(&noisy_sine(100));
+    y[30] += 10.0;
+
+    let mut smoother = WhittakerSmoother::new(1e2_f32, 2, y.len(), None, None).unwrap();
+    let result = smoother
+        .smooth_robust(&y, RobustLoss::default(), 20, 1e-4)
+        .unwrap();
+
+    assert!(result.robust_weights[30] < 0.01);
+    assert!((result.smoothed[30] - y[29]).abs() < 1.0);
+}
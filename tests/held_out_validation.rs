@@ -0,0 +1,109 @@
+use crate::common::{autocorrelated_sine, noisy_sine};
+use approx::assert_relative_eq;
+use whittaker_eilers::{SelectionCriterion, WhittakerError, WhittakerSmoother};
+
+/// Holds out each fold with `update_weights` and returns the square-rooted weighted mean squared error.
+fn manual_held_out_error(
+    y: &[f64],
+    weights: &[f64],
+    lambda: f64,
+    fold_of: impl Fn(usize) -> usize,
+    folds: usize,
+) -> f64 {
+    let mut smoother =
+        WhittakerSmoother::new(lambda, 2, y.len(), None, Some(&weights.to_vec())).unwrap();
+    let mut squared_error = 0.0;
+    for fold in 0..folds {
+        let fold_weights = (0..y.len())
+            .map(|i| if fold_of(i) == fold { 0.0 } else { weights[i] })
+            .collect::<Vec<f64>>();
+        smoother.update_weights(&fold_weights).unwrap();
+        let predicted = smoother.smooth(y).unwrap();
+        for i in (0..y.len()).filter(|i| fold_of(*i) == fold) {
+            squared_error += weights[i] * (y[i] - predicted[i]).powi(2);
+        }
+    }
+    (squared_error / weights.iter().sum::<f64>()).sqrt()
+}
+
+#[test]
+fn k_fold_and_blocked_scores_match_manual_hold_out() {
+    let y = noisy_sine(103);
+    let mut weights = vec![1.0; y.len()];
+    weights[10] = 0.0;
+    weights[50] = 0.5;
+    let lambda = 150.0;
+
+    let smoother = WhittakerSmoother::new(lambda, 2, y.len(), None, Some(&weights)).unwrap();
+    let before = smoother.smooth(&y).unwrap();
+
+    let k_fold = smoother
+        .smooth_and_score(&y, SelectionCriterion::KFold(5))
+        .unwrap();
+    assert_relative_eq!(
+        k_fold.score,
+        manual_held_out_error(&y, &weights, lambda, |i| i % 5, 5),
+        max_relative = 1e-10
+    );
+
+    let blocked = smoother
+        .smooth_and_score(&y, SelectionCriterion::BlockedCrossValidation(4))
+        .unwrap();
+    assert_relative_eq!(
+        blocked.score,
+        manual_held_out_error(&y, &weights, lambda, |i| i * 4 / y.len(), 4),
+        max_relative = 1e-10
+    );
+
+    assert_eq!(k_fold.smoothed, before);
+    assert_eq!(
+        k_fold.cross_validation_error,
+        smoother
+            .smooth_and_cross_validate(&y)
+            .unwrap()
+            .cross_validation_error
+    );
+    assert_eq!(smoother.smooth(&y).unwrap(), before);
+}
+
+#[test]
+fn blocked_cross_validation_smooths_autocorrelated_data() {
+    let y = autocorrelated_sine(400, 987654321);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let leave_one_out = smoother
+        .smooth_optimal_with_criterion(&y, false, SelectionCriterion::CrossValidation)
+        .unwrap();
+    let k_fold = smoother
+        .smooth_optimal_with_criterion(&y, false, SelectionCriterion::KFold(10))
+        .unwrap();
+    let blocked = smoother
+        .smooth_optimal_with_criterion(&y, false, SelectionCriterion::BlockedCrossValidation(20))
+        .unwrap();
+
+    assert_eq!(
+        blocked.validation_results.len(),
+        leave_one_out.validation_results.len()
+    );
+    assert!(k_fold.get_optimal().lambda < 10.0 * leave_one_out.get_optimal().lambda);
+    assert!(blocked.get_optimal().lambda > 100.0 * leave_one_out.get_optimal().lambda);
+}
+
+#[test]
+fn invalid_fold_counts_are_rejected() {
+    let y = noisy_sine(30);
+    let smoother = WhittakerSmoother::new(10.0, 2, y.len(), None, None).unwrap();
+
+    assert_eq!(
+        smoother
+            .smooth_and_score(&y, SelectionCriterion::KFold(1))
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("folds")
+    );
+    assert_eq!(
+        smoother
+            .smooth_and_score(&y, SelectionCriterion::BlockedCrossValidation(31))
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("blocks")
+    );
+}
@@ -0,0 +1,74 @@
+use whittaker_eilers::{WhittakerError, WhittakerSmoother};
+
+#[test]
+fn invalid_construction() {
+    let x = (0..10).map(|i| i as f64).collect::<Vec<f64>>();
+    let weights = vec![1.0; 10];
+
+    for lambda in [-1.0, f64::NAN, f64::INFINITY] {
+        assert_eq!(
+            WhittakerSmoother::new(lambda, 2, 10, Some(&x), Some(&weights)).err(),
+            Some(WhittakerError::InvalidParameter("lambda"))
+        );
+    }
+
+    for (value, index) in [(f64::NAN, 3), (-0.5, 0), (f64::INFINITY, 9)] {
+        let mut invalid = weights.clone();
+        invalid[index] = value;
+        assert_eq!(
+            WhittakerSmoother::new(1e2, 2, 10, Some(&x), Some(&invalid)).err(),
+            Some(WhittakerError::InvalidValue("weights", index))
+        );
+    }
+
+    for (value, index) in [(f64::NAN, 3), (f64::NEG_INFINITY, 0), (f64::INFINITY, 9)] {
+        let mut invalid = x.clone();
+        invalid[index] = value;
+        assert_eq!(
+            WhittakerSmoother::new(1e2, 2, 10, Some(&invalid), None).err(),
+            Some(WhittakerError::InvalidValue("x_input", index))
+        );
+    }
+
+    assert_eq!(
+        WhittakerSmoother::new(1e2, 2, 10, None, Some(&vec![0.0; 10])).err(),
+        Some(WhittakerError::AllWeightsZero)
+    );
+}
+
+#[test]
+fn invalid_updates_and_data() {
+    let y = (0..10).map(|i| (i as f64).sqrt()).collect::<Vec<f64>>();
+    let mut smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+    let smoothed = smoother.smooth(&y).unwrap();
+
+    // Invalid updates are rejected without changing the smoother.
+    assert_eq!(
+        smoother.update_lambda(-1e2).unwrap_err(),
+        WhittakerError::InvalidParameter("lambda")
+    );
+    assert_eq!(
+        smoother
+            .update_weights(&vec![1.0, f64::NAN, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0])
+            .unwrap_err(),
+        WhittakerError::InvalidValue("weights", 1)
+    );
+    assert_eq!(
+        smoother.update_weights(&vec![0.0; 10]).unwrap_err(),
+        WhittakerError::AllWeightsZero
+    );
+    assert_eq!(smoother.get_lambda(), 1e2);
+    assert_eq!(smoother.smooth(&y).unwrap(), smoothed);
+
+    let mut with_nan = y.clone();
+    with_nan[6] = f64::NAN;
+    let error = smoother.smooth(&with_nan).unwrap_err();
+    assert_eq!(error, WhittakerError::InvalidValue("y_input", 6));
+    assert!(error.to_string().contains("y_input"));
+    assert!(error.to_string().contains('6'));
+
+    assert_eq!(
+        smoother.smooth_missing(&[f64::NAN; 10]).unwrap_err(),
+        WhittakerError::AllWeightsZero
+    );
+}
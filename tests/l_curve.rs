@@ -0,0 +1,96 @@
+use crate::common::noisy_sine;
+use approx::assert_relative_eq;
+use whittaker_eilers::{CornerMethod, WhittakerError, WhittakerSmoother};
+
+fn log_spaced(start: f64, end: f64, step: f64) -> Vec<f64> {
+    let steps = ((end - start) / step).round() as usize;
+    (0..=steps)
+        .map(|i| 10_f64.powf(start + step * i as f64))
+        .collect()
+}
+
+#[test]
+fn l_curve_norms_match_smoothed_data() {
+    let y = noisy_sine(120);
+    let mut weights = vec![1.0; y.len()];
+    weights[40] = 0.0;
+    weights[41] = 0.25;
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, Some(&weights)).unwrap();
+
+    let result = smoother
+        .l_curve(&y, &[1e0, 1e2, 1e4], CornerMethod::MaximumCurvature)
+        .unwrap();
+
+    for point in &result.points {
+        smoother.update_lambda(point.lambda).unwrap();
+        let z = smoother.smooth(&y).unwrap();
+        assert_eq!(point.smoothed, z);
+
+        let residual_norm = (0..y.len())
+            .map(|i| weights[i] * (y[i] - z[i]).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        let penalty_norm = z
+            .windows(3)
+            .map(|w| (w[0] - 2.0 * w[1] + w[2]).powi(2))
+            .sum::<f64>()
+            .sqrt();
+
+        assert_relative_eq!(point.residual_norm, residual_norm, max_relative = 1e-10);
+        assert_relative_eq!(point.penalty_norm, penalty_norm, max_relative = 1e-10);
+    }
+    assert_eq!(result.corner.lambda, 1e2);
+}
+
+#[test]
+fn corners_are_found_between_the_arms() {
+    let y = noisy_sine(300);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+    let lambdas = log_spaced(-2.0, 8.0, 0.1);
+
+    let curvature = smoother
+        .l_curve(&y, &lambdas, CornerMethod::MaximumCurvature)
+        .unwrap();
+    assert_eq!(curvature.points.len(), lambdas.len());
+    assert!(curvature.corner.lambda > 1e1 && curvature.corner.lambda < 1e3);
+    assert!(curvature
+        .points
+        .iter()
+        .any(|point| point.lambda == curvature.corner.lambda));
+
+    let v_curve = smoother.l_curve(&y, &lambdas, CornerMethod::VCurve).unwrap();
+    assert!(v_curve.corner.lambda > 1e1 && v_curve.corner.lambda < 1e3);
+
+    // The V-curve corner lies halfway between two tested lambdas.
+    let index = lambdas
+        .iter()
+        .position(|lambda| *lambda > v_curve.corner.lambda)
+        .unwrap();
+    assert_relative_eq!(
+        v_curve.corner.lambda,
+        (lambdas[index - 1] * lambdas[index]).sqrt(),
+        max_relative = 1e-12
+    );
+
+    smoother.update_lambda(v_curve.corner.lambda).unwrap();
+    assert_eq!(v_curve.corner.smoothed, smoother.smooth(&y).unwrap());
+}
+
+#[test]
+fn l_curve_rejects_invalid_lambdas() {
+    let y = noisy_sine(50);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    for (lambdas, method) in [
+        (vec![1e1, 1e2], CornerMethod::MaximumCurvature),
+        (vec![1e1], CornerMethod::VCurve),
+        (vec![1e1, 1e3, 1e2], CornerMethod::VCurve),
+        (vec![0.0, 1e1, 1e2], CornerMethod::MaximumCurvature),
+        (vec![1e1, f64::NAN, 1e2], CornerMethod::MaximumCurvature),
+    ] {
+        assert_eq!(
+            smoother.l_curve(&y, &lambdas, method).unwrap_err(),
+            WhittakerError::InvalidParameter("lambdas")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{WhittakerError, WhittakerSmoother};
+
+fn signal() -> Vec<f64> {
+    (0..150)
+        .map(|i| (i as f64 / 12.0).sin() + 0.3 * ((i * 7919) % 13) as f64 / 13.0)
+        .collect()
+}
+
+/// Blanks out samples, as a sensor dropping out would.
+fn with_gaps(y: &[f64], gaps: &[usize], value: f64) -> Vec<f64> {
+    let mut gappy = y.to_vec();
+    for i in gaps {
+        gappy[*i] = value;
+    }
+    gappy
+}
+
+#[test]
+fn missing_values_are_interpolated() {
+    let y = signal();
+    let gaps = [0, 40, 41, 42, 43, 99, 149];
+    let x = (0..y.len())
+        .map(|i| i as f64 + 0.3 * (i as f64).cos())
+        .collect::<Vec<f64>>();
+    let weights = (0..y.len())
+        .map(|i| 1.0 + (i % 3) as f64)
+        .collect::<Vec<f64>>();
+
+    let mut smoother = WhittakerSmoother::new(1e2, 2, y.len(), Some(&x), Some(&weights)).unwrap();
+
+    // Missing samples are given zero weight, on top of the smoother's own weights.
+    let zero_weights = with_gaps(&weights, &gaps, 0.0);
+    let expected = WhittakerSmoother::new(1e2, 2, y.len(), Some(&x), Some(&zero_weights))
+        .unwrap()
+        .smooth(&with_gaps(&y, &gaps, 0.0))
+        .unwrap();
+
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let result = smoother
+            .smooth_missing(&with_gaps(&y, &gaps, value))
+            .unwrap();
+
+        assert_eq!(
+            result.imputed,
+            (0..y.len())
+                .map(|i| gaps.contains(&i))
+                .collect::<Vec<bool>>()
+        );
+        for (a, b) in result.smoothed.iter().zip(&expected) {
+            assert!(a.is_finite());
+            assert_relative_eq!(*a, *b, epsilon = 1e-10);
+        }
+    }
+
+    // The smoother itself is unchanged.
+    let smoothed = smoother.smooth(&y).unwrap();
+    let fresh = WhittakerSmoother::new(1e2, 2, y.len(), Some(&x), Some(&weights)).unwrap();
+    assert_eq!(smoothed, fresh.smooth(&y).unwrap());
+}
+
+#[test]
+fn missing_pattern_changes() {
+    let y = signal();
+    let mut smoother = WhittakerSmoother::new(1e3, 3, y.len(), None, None).unwrap();
+
+    let expected = |gaps: &[usize], lambda: f64| {
+        let weights = with_gaps(&vec![1.0; y.len()], gaps, 0.0);
+        WhittakerSmoother::new(lambda, 3, y.len(), None, Some(&weights))
+            .unwrap()
+            .smooth(&with_gaps(&y, gaps, 0.0))
+            .unwrap()
+    };
+
+    // Alternating between patterns, including none missing, always matches a fresh smoother.
+    for (gaps, lambda) in [
+        (vec![10, 11], 1e3),
+        (vec![10, 11], 1e3),
+        (vec![], 1e3),
+        (vec![70], 1e3),
+        (vec![70], 1e1),
+    ] {
+        smoother.update_lambda(lambda).unwrap();
+        let result = smoother
+            .smooth_missing(&with_gaps(&y, &gaps, f64::NAN))
+            .unwrap();
+
+        assert_eq!(result.imputed.iter().filter(|m| **m).count(), gaps.len());
+        for (a, b) in result.smoothed.iter().zip(&expected(&gaps, lambda)) {
+            assert_relative_eq!(*a, *b, epsilon = 1e-10);
+        }
+    }
+
+    // A pattern with nothing left to smooth fails without disturbing the previous one.
+    assert_eq!(
+        smoother
+            .smooth_missing(&vec![f64::NAN; y.len()])
+            .unwrap_err(),
+        WhittakerError::AllWeightsZero
+    );
+    let result = smoother
+        .smooth_missing(&with_gaps(&y, &[70], f64::NAN))
+        .unwrap();
+    for (a, b) in result.smoothed.iter().zip(&expected(&[70], 1e1)) {
+        assert_relative_eq!(*a, *b, epsilon = 1e-10);
+    }
+
+    assert_eq!(
+        smoother.smooth_missing(&y[1..]).unwrap_err(),
+        WhittakerError::LengthMismatch(y.len(), y.len() - 1)
+    );
+}
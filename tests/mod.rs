@@ -1,5 +1,80 @@
+#[cfg(test)]
+mod common;
+
 #[cfg(test)]
 mod validation;
 
 #[cfg(test)]
 mod robustness;
+
+#[cfg(test)]
+mod robust_smoothing;
+
+#[cfg(test)]
+mod baseline;
+
+#[cfg(test)]
+mod smoothing_2d;
+
+#[cfg(test)]
+mod confidence_bands;
+
+#[cfg(test)]
+mod selection_criteria;
+
+#[cfg(test)]
+mod continuous_optimisation;
+
+#[cfg(test)]
+mod optimal_options;
+
+#[cfg(test)]
+mod held_out_validation;
+
+#[cfg(test)]
+mod l_curve;
+
+#[cfg(test)]
+mod float32;
+
+#[cfg(test)]
+mod banded_solver;
+
+#[cfg(test)]
+mod refactorisation;
+
+#[cfg(test)]
+mod prediction;
+
+#[cfg(test)]
+mod derivative;
+
+#[cfg(test)]
+mod bounded_smoothing;
+
+#[cfg(test)]
+mod shape_constraints;
+
+#[cfg(test)]
+mod penalty_weights;
+
+#[cfg(test)]
+mod penalty_terms;
+
+#[cfg(test)]
+mod periodic;
+
+#[cfg(test)]
+mod missing_values;
+
+#[cfg(test)]
+mod input_validation;
+
+#[cfg(test)]
+mod batch_smoothing;
+
+#[cfg(test)]
+mod block_smoothing;
+
+#[cfg(test)]
+mod ndarray_integration;
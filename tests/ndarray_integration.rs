@@ -0,0 +1,80 @@
+#![cfg(feature = "ndarray")]
+
+use approx::assert_relative_eq;
+use ndarray::{s, Array1, Array2, Axis, ShapeBuilder};
+use whittaker_eilers::{WhittakerError, WhittakerSmoother};
+
+fn series(i: usize, j: usize) -> f64 {
+    (i as f64 / (4.0 + (j % 5) as f64)).sin() + 0.2 * ((i * 13 + j * 7) % 11) as f64 / 11.0
+}
+
+#[test]
+fn smooth_array_views() {
+    let x = Array1::from_shape_fn(100, |i| i as f64 / 2.0 + 0.1 * (i as f64).sin());
+    let weights = Array1::from_shape_fn(100, |i| if i % 7 == 3 { 0.0 } else { 1.0 });
+    let y = Array1::from_shape_fn(100, |i| series(i, 0));
+
+    // Every other element, so none of the views are contiguous.
+    let x_view = x.slice(s![..;2]);
+    let weights_view = weights.slice(s![..;2]);
+    let y_view = y.slice(s![..;2]);
+
+    let mut smoother =
+        WhittakerSmoother::new_from_arrays(1e2, 2, 50, Some(x_view), Some(weights_view)).unwrap();
+    let expected = WhittakerSmoother::new(
+        1e2,
+        2,
+        50,
+        Some(&x_view.to_vec()),
+        Some(&weights_view.to_vec()),
+    )
+    .unwrap()
+    .smooth(&y_view.to_vec())
+    .unwrap();
+
+    assert_eq!(smoother.smooth_array(y_view).unwrap().to_vec(), expected);
+
+    smoother
+        .update_weights_array(Array1::ones(50).view())
+        .unwrap();
+    assert_eq!(
+        smoother.smooth_array(y_view).unwrap().to_vec(),
+        smoother.smooth(&y_view.to_vec()).unwrap()
+    );
+}
+
+#[test]
+fn smooth_along_axis() {
+    let smoother = WhittakerSmoother::new(1e3, 2, 60, None, None).unwrap();
+
+    // Enough series to be split over several tiles, in both memory orders.
+    let columns = Array2::from_shape_fn((60, 1000), |(i, j)| series(i, j));
+    let columns_fortran = Array2::from_shape_fn((60, 1000).f(), |(i, j)| series(i, j));
+    let rows = columns.t().to_owned();
+
+    for (y, axis) in [
+        (columns.view(), Axis(0)),
+        (columns_fortran.view(), Axis(0)),
+        (rows.view(), Axis(1)),
+        (columns.slice(s![.., ..;3]), Axis(0)),
+    ] {
+        let smoothed = smoother.smooth_axis(y, axis).unwrap();
+        assert_eq!(smoothed.dim(), y.dim());
+
+        for (lane, smoothed_lane) in y.lanes(axis).into_iter().zip(smoothed.lanes(axis)) {
+            let expected = smoother.smooth(&lane.to_vec()).unwrap();
+            for (a, b) in smoothed_lane.iter().zip(&expected) {
+                assert_relative_eq!(*a, *b, epsilon = 1e-10);
+            }
+        }
+    }
+
+    assert_eq!(
+        smoother.smooth_axis(rows.view(), Axis(0)).unwrap_err(),
+        WhittakerError::LengthMismatch(60, 1000)
+    );
+    assert_eq!(
+        smoother.smooth_axis(columns.view(), Axis(2)).unwrap_err(),
+        WhittakerError::InvalidParameter("axis")
+    );
+}
@@ -0,0 +1,237 @@
+use crate::common::{autocorrelated_sine, noisy_sine};
+use whittaker_eilers::{
+    LambdaSearch, SelectionCriterion, SerialCorrelation, SmoothOptimalOptions, WhittakerError,
+    WhittakerSmoother,
+};
+
+#[test]
+fn default_options_match_smooth_optimal() {
+    let y = noisy_sine(200);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let expected = smoother.smooth_optimal(&y, true).unwrap();
+    let results = smoother
+        .smooth_optimal_with_options(&y, &SmoothOptimalOptions::default())
+        .unwrap();
+
+    assert_eq!(
+        results.validation_results.len(),
+        expected.validation_results.len()
+    );
+    for (result, expected) in results
+        .validation_results
+        .iter()
+        .zip(&expected.validation_results)
+    {
+        assert_eq!(result.lambda, expected.lambda);
+        assert_eq!(result.score, expected.score);
+        assert_eq!(result.smoothed, expected.smoothed);
+    }
+    assert_eq!(results.get_optimal().lambda, expected.get_optimal().lambda);
+}
+
+#[test]
+fn lambda_grid_and_range() {
+    let y = noisy_sine(100);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let lambdas = vec![1e3, 1e1, 1e2];
+    let results = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                lambdas: LambdaSearch::Grid(lambdas.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        results
+            .validation_results
+            .iter()
+            .map(|result| result.lambda)
+            .collect::<Vec<f64>>(),
+        lambdas
+    );
+
+    let results = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                lambdas: LambdaSearch::Range {
+                    min: 1e1,
+                    max: 1e5,
+                    steps: 9,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(results.validation_results.len(), 9);
+    for (index, result) in results.validation_results.iter().enumerate() {
+        approx::assert_relative_eq!(result.lambda.log10(), 1.0 + 0.5 * index as f64);
+    }
+}
+
+#[test]
+fn index_mask_matches_decimation() {
+    let y = noisy_sine(200);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let options = SmoothOptimalOptions {
+        criterion: SelectionCriterion::GeneralizedCrossValidation,
+        serial_correlation: SerialCorrelation::Decimate(3),
+        ..Default::default()
+    };
+    let decimated = smoother.smooth_optimal_with_options(&y, &options).unwrap();
+
+    let mask = (0..y.len()).map(|i| i % 3 == 0).collect::<Vec<bool>>();
+    let masked = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                serial_correlation: SerialCorrelation::IndexMask(mask),
+                ..options
+            },
+        )
+        .unwrap();
+
+    for (a, b) in decimated
+        .validation_results
+        .iter()
+        .zip(&masked.validation_results)
+    {
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.smoothed, b.smoothed);
+    }
+
+    let full = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                criterion: SelectionCriterion::GeneralizedCrossValidation,
+                serial_correlation: SerialCorrelation::Ignore,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let every_element = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                criterion: SelectionCriterion::GeneralizedCrossValidation,
+                serial_correlation: SerialCorrelation::Decimate(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    for (a, b) in full
+        .validation_results
+        .iter()
+        .zip(&every_element.validation_results)
+    {
+        approx::assert_relative_eq!(a.score, b.score, max_relative = 1e-10);
+    }
+}
+
+#[test]
+fn block_cross_validation_resists_autocorrelation() {
+    let y = autocorrelated_sine(400, 12345);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let leave_one_out = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                serial_correlation: SerialCorrelation::Ignore,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .get_optimal();
+
+    let blocked = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                serial_correlation: SerialCorrelation::BlockCrossValidation(20),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .get_optimal();
+
+    assert!(blocked.lambda > 100.0 * leave_one_out.lambda);
+    assert_eq!(
+        blocked.criterion,
+        SelectionCriterion::BlockedCrossValidation(20)
+    );
+    assert_eq!(blocked.smoothed, {
+        smoother.update_lambda(blocked.lambda).unwrap();
+        smoother.smooth(&y).unwrap()
+    });
+}
+
+#[test]
+fn invalid_options_are_rejected() {
+    let y = noisy_sine(50);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let cases = [
+        (
+            SmoothOptimalOptions {
+                lambdas: LambdaSearch::Grid(vec![]),
+                ..Default::default()
+            },
+            WhittakerError::InvalidParameter("lambdas"),
+        ),
+        (
+            SmoothOptimalOptions {
+                lambdas: LambdaSearch::Grid(vec![1.0, -1.0]),
+                ..Default::default()
+            },
+            WhittakerError::InvalidParameter("lambdas"),
+        ),
+        (
+            SmoothOptimalOptions {
+                lambdas: LambdaSearch::Range {
+                    min: 1.0,
+                    max: 1e3,
+                    steps: 1,
+                },
+                ..Default::default()
+            },
+            WhittakerError::InvalidParameter("steps"),
+        ),
+        (
+            SmoothOptimalOptions {
+                serial_correlation: SerialCorrelation::Decimate(0),
+                ..Default::default()
+            },
+            WhittakerError::InvalidParameter("decimation"),
+        ),
+        (
+            SmoothOptimalOptions {
+                serial_correlation: SerialCorrelation::IndexMask(vec![true; 10]),
+                ..Default::default()
+            },
+            WhittakerError::LengthMismatch(50, 10),
+        ),
+        (
+            SmoothOptimalOptions {
+                serial_correlation: SerialCorrelation::BlockCrossValidation(1),
+                ..Default::default()
+            },
+            WhittakerError::InvalidParameter("blocks"),
+        ),
+    ];
+
+    for (options, error) in cases {
+        assert_eq!(
+            smoother
+                .smooth_optimal_with_options(&y, &options)
+                .unwrap_err(),
+            error
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use approx::assert_relative_eq;
+use sprs::{CsMat, TriMat};
+use whittaker_eilers::{
+    PenaltyTerm, SerialCorrelation, SmoothOptimalOptions, WhittakerError, WhittakerSmoother,
+};
+
+fn noise(i: usize) -> f64 {
+    0.4 * ((i * 7919) % 19) as f64 / 19.0 - 0.2
+}
+
+/// A harmonic penalty `D'D` whose null space holds every sinusoid with the given period.
+fn harmonic_penalty(data_length: usize, period: f64) -> CsMat<f64> {
+    let c = (2.0 * std::f64::consts::PI / period).cos();
+    let mut d_mat = TriMat::new((data_length - 2, data_length));
+    for row in 0..data_length - 2 {
+        d_mat.add_triplet(row, row, 1.0);
+        d_mat.add_triplet(row, row + 1, -2.0 * c);
+        d_mat.add_triplet(row, row + 2, 1.0);
+    }
+    let d_mat: CsMat<f64> = d_mat.to_csr();
+    &d_mat.transpose_view() * &d_mat
+}
+
+#[test]
+fn mixed_order_penalty() {
+    let y = (0..100)
+        .map(|i| (i as f64 / 10.0).sin() + noise(i))
+        .collect::<Vec<f64>>();
+    let x = (0..100)
+        .map(|i| i as f64 + 0.3 * (i as f64 * 0.7).sin())
+        .collect::<Vec<f64>>();
+
+    // A zero lambda term changes nothing.
+    let single = WhittakerSmoother::new(1e2, 2, y.len(), Some(&x), None).unwrap();
+    let with_empty_term = WhittakerSmoother::new_with_penalties(
+        &[
+            PenaltyTerm::Difference {
+                order: 2,
+                lambda: 1e2,
+            },
+            PenaltyTerm::Difference {
+                order: 1,
+                lambda: 0.0,
+            },
+        ],
+        y.len(),
+        Some(&x),
+        None,
+    )
+    .unwrap();
+    for (a, b) in single
+        .smooth(&y)
+        .unwrap()
+        .iter()
+        .zip(&with_empty_term.smooth(&y).unwrap())
+    {
+        assert_relative_eq!(*a, *b, epsilon = 1e-10);
+    }
+
+    // Damping the slope as well flattens the smoothed data.
+    let penalties = [
+        PenaltyTerm::Difference {
+            order: 2,
+            lambda: 1e2,
+        },
+        PenaltyTerm::Difference {
+            order: 1,
+            lambda: 1e2,
+        },
+    ];
+    let mut mixed =
+        WhittakerSmoother::new_with_penalties(&penalties, y.len(), Some(&x), None).unwrap();
+    assert_eq!(mixed.get_penalties(), penalties);
+
+    let slope = |z: &[f64]| z.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>();
+    assert!(slope(&mixed.smooth(&y).unwrap()) < 0.5 * slope(&single.smooth(&y).unwrap()));
+
+    // Updating the main term keeps the others, matching a smoother made with the updated terms.
+    mixed.update_order(3).unwrap();
+    mixed.update_lambda(1e3).unwrap();
+    let updated = [
+        PenaltyTerm::Difference {
+            order: 3,
+            lambda: 1e3,
+        },
+        penalties[1].clone(),
+    ];
+    assert_eq!(mixed.get_penalties(), updated);
+
+    let fresh = WhittakerSmoother::new_with_penalties(&updated, y.len(), Some(&x), None).unwrap();
+    assert_eq!(mixed.smooth(&y).unwrap(), fresh.smooth(&y).unwrap());
+
+    mixed.update_penalties(&penalties).unwrap();
+    let original =
+        WhittakerSmoother::new_with_penalties(&penalties, y.len(), Some(&x), None).unwrap();
+    assert_eq!(mixed.smooth(&y).unwrap(), original.smooth(&y).unwrap());
+}
+
+#[test]
+fn harmonic_penalty_keeps_seasonal_cycle() {
+    let period = 12.0;
+    let seasonal = (0..120)
+        .map(|i| 3.0 * (2.0 * std::f64::consts::PI * i as f64 / period + 0.4).sin())
+        .collect::<Vec<f64>>();
+    let y = seasonal
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s + noise(i))
+        .collect::<Vec<f64>>();
+
+    let mut smoother = WhittakerSmoother::new_with_penalties(
+        &[
+            PenaltyTerm::Difference {
+                order: 2,
+                lambda: 1e-6,
+            },
+            PenaltyTerm::Matrix {
+                matrix: harmonic_penalty(y.len(), period),
+                lambda: 1e5,
+            },
+        ],
+        y.len(),
+        None,
+        None,
+    )
+    .unwrap();
+    let harmonic = smoother.smooth(&y).unwrap();
+
+    let second_order = WhittakerSmoother::new(1e5, 2, y.len(), None, None)
+        .unwrap()
+        .smooth(&y)
+        .unwrap();
+
+    let error = |z: &[f64]| {
+        z.iter()
+            .zip(&seasonal)
+            .map(|(z, s)| (z - s).powi(2))
+            .sum::<f64>()
+            .sqrt()
+            / (z.len() as f64).sqrt()
+    };
+    assert!(error(&harmonic) < 0.1);
+    assert!(error(&second_order) > 1.0);
+
+    // The main lambda can still be selected, as long as the data isn't subsampled.
+    let options = SmoothOptimalOptions {
+        serial_correlation: SerialCorrelation::Ignore,
+        ..Default::default()
+    };
+    assert!(smoother.smooth_optimal_with_options(&y, &options).is_ok());
+    assert_eq!(
+        smoother.smooth_optimal(&y, true).unwrap_err(),
+        WhittakerError::InvalidParameter("penalties")
+    );
+    assert_eq!(
+        smoother.predict(&y, &[3.5]).unwrap_err(),
+        WhittakerError::InvalidParameter("penalties")
+    );
+}
+
+#[test]
+fn invalid_penalty_terms() {
+    let matrix_first = [PenaltyTerm::Matrix {
+        matrix: harmonic_penalty(10, 4.0),
+        lambda: 1.0,
+    }];
+    assert_eq!(
+        WhittakerSmoother::new_with_penalties(&matrix_first, 10, None, None).err(),
+        Some(WhittakerError::InvalidParameter("penalties"))
+    );
+    assert_eq!(
+        WhittakerSmoother::<f64>::new_with_penalties(&[], 10, None, None).err(),
+        Some(WhittakerError::InvalidParameter("penalties"))
+    );
+
+    let main = PenaltyTerm::Difference {
+        order: 2,
+        lambda: 1.0,
+    };
+    let mut asymmetric = TriMat::new((10, 10));
+    asymmetric.add_triplet(0, 1, 1.0);
+    let cases = [
+        (
+            PenaltyTerm::Matrix {
+                matrix: harmonic_penalty(9, 4.0),
+                lambda: 1.0,
+            },
+            WhittakerError::LengthMismatch(10, 9),
+        ),
+        (
+            PenaltyTerm::Matrix {
+                matrix: asymmetric.to_csr(),
+                lambda: 1.0,
+            },
+            WhittakerError::InvalidParameter("penalties"),
+        ),
+        (
+            PenaltyTerm::Difference {
+                order: 1,
+                lambda: -1.0,
+            },
+            WhittakerError::InvalidParameter("lambda"),
+        ),
+        (
+            PenaltyTerm::Difference {
+                order: 11,
+                lambda: 1.0,
+            },
+            WhittakerError::DataTooShort(10, 11),
+        ),
+    ];
+
+    let mut smoother = WhittakerSmoother::new(1.0, 2, 10, None, None).unwrap();
+    for (term, error) in cases {
+        let penalties = [main.clone(), term];
+        assert_eq!(
+            WhittakerSmoother::new_with_penalties(&penalties, 10, None, None).err(),
+            Some(error.clone())
+        );
+        assert_eq!(smoother.update_penalties(&penalties).unwrap_err(), error);
+    }
+    assert_eq!(smoother.get_penalties(), [main]);
+}
@@ -0,0 +1,141 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{SelectionCriterion, WhittakerError, WhittakerSmoother};
+
+fn noise(i: usize) -> f64 {
+    0.1 * ((i * 7919) % 17) as f64 / 17.0 - 0.05
+}
+
+/// A flat chromatogram with a single narrow peak at index 100.
+fn chromatogram() -> (Vec<f64>, Vec<f64>) {
+    let clean = (0..200)
+        .map(|i| 5.0 * (-((i as f64 - 100.0) / 3.0).powi(2)).exp())
+        .collect::<Vec<f64>>();
+    let noisy = clean
+        .iter()
+        .enumerate()
+        .map(|(i, y)| y + noise(i))
+        .collect::<Vec<f64>>();
+    (clean, noisy)
+}
+
+#[test]
+fn constant_penalty_weights_scale_lambda() {
+    let (_, y) = chromatogram();
+    let x = (0..y.len())
+        .map(|i| i as f64 + 0.2 * (i as f64).sin())
+        .collect::<Vec<f64>>();
+
+    let mut smoother = WhittakerSmoother::new(1e2, 2, y.len(), Some(&x), None).unwrap();
+    smoother
+        .update_penalty_weights(&vec![4.0; y.len()])
+        .unwrap();
+    let weighted = smoother
+        .smooth_and_score(&y, SelectionCriterion::Reml)
+        .unwrap();
+
+    let unweighted = WhittakerSmoother::new(4e2, 2, y.len(), Some(&x), None)
+        .unwrap()
+        .smooth_and_score(&y, SelectionCriterion::Reml)
+        .unwrap();
+
+    for (a, b) in weighted.smoothed.iter().zip(&unweighted.smoothed) {
+        assert_relative_eq!(*a, *b, epsilon = 1e-8);
+    }
+    assert_relative_eq!(weighted.score, unweighted.score, epsilon = 1e-8);
+    assert_relative_eq!(
+        weighted.cross_validation_error,
+        unweighted.cross_validation_error,
+        epsilon = 1e-8
+    );
+}
+
+#[test]
+fn penalty_weights_survive_updates() {
+    let y = (0..200).map(noise).collect::<Vec<f64>>();
+    let penalty_weights = (0..y.len())
+        .map(|i| if i < 100 { 10.0 } else { 0.1 })
+        .collect::<Vec<f64>>();
+
+    let mut smoother = WhittakerSmoother::new(1e2, 3, y.len(), None, None).unwrap();
+    smoother.update_penalty_weights(&penalty_weights).unwrap();
+    smoother.update_order(2).unwrap();
+    smoother.update_weights(&vec![1.0; y.len()]).unwrap();
+    smoother.update_lambda(1e3).unwrap();
+
+    let mut fresh = WhittakerSmoother::new(1e3, 2, y.len(), None, None).unwrap();
+    fresh.update_penalty_weights(&penalty_weights).unwrap();
+
+    assert_eq!(smoother.smooth(&y).unwrap(), fresh.smooth(&y).unwrap());
+
+    // The first half is penalised more heavily than without penalty weights, so it is smoother, and the second half less.
+    let unweighted = WhittakerSmoother::new(1e3, 2, y.len(), None, None)
+        .unwrap()
+        .smooth(&y)
+        .unwrap();
+    let weighted = fresh.smooth(&y).unwrap();
+    let roughness = |z: &[f64], range: std::ops::Range<usize>| {
+        z[range]
+            .windows(3)
+            .map(|w| (w[2] - 2.0 * w[1] + w[0]).powi(2))
+            .sum::<f64>()
+    };
+    assert!(roughness(&weighted, 10..60) < roughness(&unweighted, 10..60));
+    assert!(roughness(&weighted, 140..190) > roughness(&unweighted, 140..190));
+
+    // Subsampled selection of lambda carries the penalty weights across.
+    let optimal = fresh.smooth_optimal(&y, true).unwrap().get_optimal();
+    fresh.update_lambda(optimal.lambda).unwrap();
+    assert_eq!(optimal.smoothed, fresh.smooth(&y).unwrap());
+}
+
+#[test]
+fn adaptive_smoothing_keeps_sharp_peak() {
+    let (clean, noisy) = chromatogram();
+
+    let mut smoother = WhittakerSmoother::new(1e2, 2, noisy.len(), None, None).unwrap();
+    let standard = smoother.smooth(&noisy).unwrap();
+
+    let adaptive = smoother.smooth_adaptive(&noisy, 10).unwrap();
+
+    // The peak is penalised less than the flat baseline.
+    assert!(adaptive.penalty_weights[100] < 0.2 * adaptive.penalty_weights[20]);
+    assert!((adaptive.smoothed[100] - clean[100]).abs() < 0.5 * (standard[100] - clean[100]).abs());
+
+    // The flat baseline is smoothed at least as well.
+    let error = |z: &[f64]| {
+        z[10..60]
+            .iter()
+            .zip(&clean[10..60])
+            .map(|(z, y)| (z - y).powi(2))
+            .sum::<f64>()
+    };
+    assert!(error(&adaptive.smoothed) <= error(&standard));
+
+    // The smoother's own penalty weights are restored, and applying the estimated ones reproduces the result.
+    assert_eq!(smoother.smooth(&noisy).unwrap(), standard);
+    smoother
+        .update_penalty_weights(&adaptive.penalty_weights)
+        .unwrap();
+    assert_eq!(smoother.smooth(&noisy).unwrap(), adaptive.smoothed);
+}
+
+#[test]
+fn invalid_penalty_weights() {
+    let mut smoother = WhittakerSmoother::new(1e2, 2, 10, None, None).unwrap();
+
+    assert_eq!(
+        smoother.update_penalty_weights(&[1.0; 9]).unwrap_err(),
+        WhittakerError::LengthMismatch(10, 9)
+    );
+
+    for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let mut penalty_weights = vec![1.0; 10];
+        penalty_weights[3] = bad;
+        assert_eq!(
+            smoother
+                .update_penalty_weights(&penalty_weights)
+                .unwrap_err(),
+            WhittakerError::InvalidParameter("penalty_weights")
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{Boundary, SelectionCriterion, WhittakerError, WhittakerSmoother};
+
+fn noise(i: usize) -> f64 {
+    0.4 * ((i * 7919) % 23) as f64 / 23.0 - 0.2
+}
+
+/// Moves the first `shift` elements to the end.
+fn rotate(data: &[f64], shift: usize) -> Vec<f64> {
+    let mut rotated = data.to_vec();
+    rotated.rotate_left(shift);
+    rotated
+}
+
+fn climatology() -> Vec<f64> {
+    (0..120)
+        .map(|i| (2.0 * std::f64::consts::PI * i as f64 / 120.0).cos() + noise(i))
+        .collect()
+}
+
+#[test]
+fn periodic_smoothing_has_no_break() {
+    let y = climatology();
+
+    let open = WhittakerSmoother::new(1e3, 2, y.len(), None, None).unwrap();
+    let mut periodic = WhittakerSmoother::new(1e3, 2, y.len(), None, None).unwrap();
+    periodic
+        .update_boundary(Boundary::Periodic { period: None })
+        .unwrap();
+    assert_eq!(periodic.get_boundary(), Boundary::Periodic { period: None });
+
+    let open_smoothed = open.smooth(&y).unwrap();
+    let smoothed = periodic.smooth(&y).unwrap();
+
+    // The wrap point is smooth, and the smoothing doesn't depend on where the series starts.
+    let wrap_step = (smoothed[0] - smoothed[y.len() - 1]).abs();
+    assert!(wrap_step < 0.01);
+    assert!((open_smoothed[0] - open_smoothed[y.len() - 1]).abs() > 5.0 * wrap_step);
+
+    let rotated = periodic.smooth(&rotate(&y, 37)).unwrap();
+    for (a, b) in rotated.iter().zip(&rotate(&smoothed, 37)) {
+        assert_relative_eq!(*a, *b, epsilon = 1e-10);
+    }
+
+    // Penalty weights wrap around the ends too.
+    let adaptive = periodic.smooth_adaptive(&y, 5).unwrap();
+    let rotated_adaptive = periodic.smooth_adaptive(&rotate(&y, 37), 5).unwrap();
+    for (a, b) in rotated_adaptive
+        .smoothed
+        .iter()
+        .zip(&rotate(&adaptive.smoothed, 37))
+    {
+        assert_relative_eq!(*a, *b, epsilon = 1e-10);
+    }
+
+    periodic.update_boundary(Boundary::Open).unwrap();
+    assert_eq!(periodic.smooth(&y).unwrap(), open_smoothed);
+}
+
+#[test]
+fn periodic_x_input() {
+    // Wind directions in degrees, sampled unequally.
+    let x = (0..90)
+        .map(|i| 4.0 * i as f64 + 1.5 * (i as f64 * 1.3).sin() + 1.0)
+        .collect::<Vec<f64>>();
+    let y = x
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (2.0 * x.to_radians()).sin() + noise(i))
+        .collect::<Vec<f64>>();
+
+    let shift = 25;
+    let rotated_x = x[shift..]
+        .iter()
+        .copied()
+        .chain(x[..shift].iter().map(|x| x + 360.0))
+        .collect::<Vec<f64>>();
+
+    let mut smoother = WhittakerSmoother::new(1e4, 3, y.len(), Some(&x), None).unwrap();
+    smoother
+        .update_boundary(Boundary::Periodic {
+            period: Some(360.0),
+        })
+        .unwrap();
+    let mut rotated_smoother =
+        WhittakerSmoother::new(1e4, 3, y.len(), Some(&rotated_x), None).unwrap();
+    rotated_smoother
+        .update_boundary(Boundary::Periodic {
+            period: Some(360.0),
+        })
+        .unwrap();
+
+    let smoothed = smoother.smooth(&y).unwrap();
+    let rotated = rotated_smoother.smooth(&rotate(&y, shift)).unwrap();
+    for (a, b) in rotated.iter().zip(&rotate(&smoothed, shift)) {
+        assert_relative_eq!(*a, *b, epsilon = 1e-8);
+    }
+
+    // Queries are wrapped into the period, so those a period from a sample match the smoothed data.
+    let prediction = smoother.predict(&y, &[x[0] + 360.0, x[5] - 720.0]).unwrap();
+    assert_relative_eq!(prediction.predicted[0], smoothed[0], epsilon = 1e-8);
+    assert_relative_eq!(prediction.predicted[1], smoothed[5], epsilon = 1e-8);
+    for (a, b) in prediction.smoothed.iter().zip(&smoothed) {
+        assert_relative_eq!(*a, *b, epsilon = 1e-8);
+    }
+
+    let prediction = smoother.predict(&y, &[-1.0, 359.0]).unwrap();
+    assert_relative_eq!(
+        prediction.predicted[0],
+        prediction.predicted[1],
+        epsilon = 1e-10
+    );
+}
+
+#[test]
+fn periodic_cross_validation() {
+    let y = climatology();
+
+    let mut smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+    smoother
+        .update_boundary(Boundary::Periodic { period: None })
+        .unwrap();
+
+    // Leave-one-out cross validation from the hat matrix matches actually leaving a point out.
+    let result = smoother.smooth_and_cross_validate(&y).unwrap();
+    let k_fold = smoother
+        .smooth_and_score(&y, SelectionCriterion::KFold(y.len()))
+        .unwrap();
+    assert_relative_eq!(
+        result.cross_validation_error,
+        k_fold.score,
+        max_relative = 1e-6
+    );
+
+    let reml = smoother
+        .smooth_and_score(&y, SelectionCriterion::Reml)
+        .unwrap();
+    assert!(reml.score.is_finite());
+
+    let optimal = smoother.smooth_optimal(&y, true).unwrap().get_optimal();
+    smoother.update_lambda(optimal.lambda).unwrap();
+    assert_eq!(optimal.smoothed, smoother.smooth(&y).unwrap());
+    assert_eq!(smoother.get_boundary(), Boundary::Periodic { period: None });
+}
+
+#[test]
+fn invalid_period() {
+    let x = vec![0.0, 90.0, 180.0, 270.0];
+    let mut equally_spaced = WhittakerSmoother::new(1.0, 2, 4, None, None).unwrap();
+    let mut unequally_spaced = WhittakerSmoother::new(1.0, 2, 4, Some(&x), None).unwrap();
+
+    assert_eq!(
+        equally_spaced
+            .update_boundary(Boundary::Periodic { period: Some(4.0) })
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("period")
+    );
+    for period in [None, Some(270.0), Some(f64::NAN)] {
+        assert_eq!(
+            unequally_spaced
+                .update_boundary(Boundary::Periodic { period })
+                .unwrap_err(),
+            WhittakerError::InvalidParameter("period")
+        );
+    }
+    assert_eq!(unequally_spaced.get_boundary(), Boundary::Open);
+}
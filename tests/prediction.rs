@@ -0,0 +1,144 @@
+use crate::common::noisy_sine;
+use approx::assert_relative_eq;
+use whittaker_eilers::{WhittakerError, WhittakerSmoother};
+
+fn irregular_positions(length: usize) -> Vec<f64> {
+    (0..length)
+        .map(|i| i as f64 + 0.4 * (i as f64 * 2.3).sin())
+        .collect()
+}
+
+#[test]
+fn prediction_matches_manually_inserted_points() {
+    let y = noisy_sine(100);
+    let x = irregular_positions(y.len());
+    let smoother = WhittakerSmoother::new(50.0, 2, y.len(), Some(&x), None).unwrap();
+
+    let queries = vec![30.5, 10.25, 70.75];
+    let result = smoother.predict(&y, &queries).unwrap();
+
+    let mut merged = x
+        .iter()
+        .map(|x| (*x, true))
+        .chain(queries.iter().map(|q| (*q, false)))
+        .collect::<Vec<(f64, bool)>>();
+    merged.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let merged_x = merged.iter().map(|(x, _)| *x).collect::<Vec<f64>>();
+    let merged_weights = merged
+        .iter()
+        .map(|(_, is_sample)| if *is_sample { 1.0 } else { 0.0 })
+        .collect::<Vec<f64>>();
+    let mut samples = y.iter();
+    let merged_y = merged
+        .iter()
+        .map(|(_, is_sample)| {
+            if *is_sample {
+                *samples.next().unwrap()
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<f64>>();
+
+    let expected = WhittakerSmoother::new(
+        50.0,
+        2,
+        merged_x.len(),
+        Some(&merged_x),
+        Some(&merged_weights),
+    )
+    .unwrap()
+    .smooth(&merged_y)
+    .unwrap();
+
+    for (query, predicted) in queries.iter().zip(&result.predicted) {
+        let index = merged_x.iter().position(|x| x == query).unwrap();
+        assert_relative_eq!(*predicted, expected[index], epsilon = 1e-12);
+    }
+
+    let expected_smoothed = expected
+        .iter()
+        .zip(&merged)
+        .filter(|(_, (_, is_sample))| *is_sample)
+        .map(|(z, _)| *z)
+        .collect::<Vec<f64>>();
+    assert_eq!(result.smoothed.len(), y.len());
+    for (actual, expected) in result.smoothed.iter().zip(&expected_smoothed) {
+        assert_relative_eq!(*actual, *expected, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn prediction_at_sample_positions_matches_smooth() {
+    let y = noisy_sine(80);
+    let mut weights = vec![1.0; y.len()];
+    weights[20] = 0.0;
+    weights[21] = 0.3;
+
+    for order in 1..=3 {
+        let smoother = WhittakerSmoother::new(1e2, order, y.len(), None, Some(&weights)).unwrap();
+        let smoothed = smoother.smooth(&y).unwrap();
+
+        // Queries on or within WHITTAKER_X_EPSILON of the implied positions 0, 1, 2, ... are not inserted.
+        let queries = vec![40.0, 3.0 + 1e-8, 0.0, 79.0, 21.0];
+        let result = smoother.predict(&y, &queries).unwrap();
+
+        for (actual, expected) in result.smoothed.iter().zip(&smoothed) {
+            assert_relative_eq!(*actual, *expected, epsilon = 1e-9);
+        }
+        for (query, predicted) in queries.iter().zip(&result.predicted) {
+            assert_relative_eq!(*predicted, smoothed[query.round() as usize], epsilon = 1e-9);
+        }
+    }
+}
+
+#[test]
+fn prediction_resamples_onto_regular_grid() {
+    let x = irregular_positions(200);
+    let y = x.iter().map(|x| (x / 15.0).sin()).collect::<Vec<f64>>();
+    let smoother = WhittakerSmoother::new(1e-1, 2, y.len(), Some(&x), None).unwrap();
+
+    let grid = (0..=190).map(|i| 5.0 + i as f64).collect::<Vec<f64>>();
+    let result = smoother.predict(&y, &grid).unwrap();
+
+    assert_eq!(result.predicted.len(), grid.len());
+    for (x, predicted) in grid.iter().zip(&result.predicted) {
+        assert_relative_eq!(*predicted, (x / 15.0).sin(), epsilon = 1e-3);
+    }
+
+    // Duplicated and unordered queries are returned in the order they were given.
+    let result = smoother.predict(&y, &[100.5, 12.5, 100.5]).unwrap();
+    assert_eq!(result.predicted[0], result.predicted[2]);
+    assert_relative_eq!(result.predicted[1], (12.5_f64 / 15.0).sin(), epsilon = 1e-3);
+}
+
+#[test]
+fn prediction_extrapolates_linearly_with_order_two() {
+    let y = (0..50).map(|i| 2.0 * i as f64 + 1.0).collect::<Vec<f64>>();
+    let smoother = WhittakerSmoother::new(1e3, 2, y.len(), None, None).unwrap();
+
+    let result = smoother.predict(&y, &[-10.0, 60.0]).unwrap();
+
+    assert_relative_eq!(result.predicted[0], -19.0, epsilon = 1e-6);
+    assert_relative_eq!(result.predicted[1], 121.0, epsilon = 1e-6);
+}
+
+#[test]
+fn prediction_rejects_invalid_input() {
+    let y = noisy_sine(30);
+    let smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, None).unwrap();
+
+    assert_eq!(
+        smoother.predict(&y, &[1.5, f64::NAN]).unwrap_err(),
+        WhittakerError::InvalidParameter("x_query")
+    );
+    assert_eq!(
+        smoother.predict(&y, &[f64::INFINITY]).unwrap_err(),
+        WhittakerError::InvalidParameter("x_query")
+    );
+    assert_eq!(
+        smoother.predict(&y[1..], &[1.5]).unwrap_err(),
+        WhittakerError::LengthMismatch(30, 29)
+    );
+}
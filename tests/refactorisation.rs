@@ -0,0 +1,80 @@
+use crate::common::noisy_sine;
+use approx::assert_relative_eq;
+use whittaker_eilers::{Boundary, WhittakerSmoother};
+
+#[test]
+fn updated_smoother_matches_new_smoother() {
+    let y = noisy_sine(150);
+    let n = y.len();
+    let x = (0..n).map(|i| i as f64 * 0.1).collect::<Vec<f64>>();
+    let mut smoother = WhittakerSmoother::new(1.0, 2, n, Some(&x), None).unwrap();
+
+    let mut weights = vec![1.0; n];
+    for (i, lambda) in [1e-2, 1e1, 1e4, 1e7, 1e2].into_iter().enumerate() {
+        weights[i * 20] = 0.0;
+        weights[i * 20 + 1] = 0.5;
+
+        smoother.update_lambda(lambda).unwrap();
+        smoother.update_weights(&weights).unwrap();
+
+        let fresh = WhittakerSmoother::new(lambda, 2, n, Some(&x), Some(&weights)).unwrap();
+
+        assert_eq!(smoother.smooth(&y).unwrap(), fresh.smooth(&y).unwrap());
+        assert_eq!(
+            smoother
+                .smooth_and_cross_validate(&y)
+                .unwrap()
+                .cross_validation_error,
+            fresh
+                .smooth_and_cross_validate(&y)
+                .unwrap()
+                .cross_validation_error
+        );
+    }
+}
+
+#[test]
+fn updated_order_matches_new_smoother() {
+    let y = noisy_sine(100);
+    let mut smoother = WhittakerSmoother::new(1e3, 1, y.len(), None, None).unwrap();
+
+    for order in [3, 2, 4, 1] {
+        smoother.update_order(order).unwrap();
+        smoother.update_lambda(1e3).unwrap();
+
+        let fresh = WhittakerSmoother::new(1e3, order, y.len(), None, None).unwrap();
+
+        assert_eq!(smoother.smooth(&y).unwrap(), fresh.smooth(&y).unwrap());
+    }
+}
+
+#[test]
+fn updated_sparse_smoother_matches_new_smoother() {
+    // Periodic boundaries use the sparse factorisation, whose numeric factor is updated in place.
+    let y = noisy_sine(120);
+    let n = y.len();
+    let boundary = Boundary::Periodic { period: None };
+    let mut smoother = WhittakerSmoother::new(1.0, 2, n, None, None).unwrap();
+    smoother.update_boundary(boundary).unwrap();
+
+    let mut weights = vec![1.0; n];
+    for (i, lambda) in [1e-2, 1e1, 1e4, 1e7, 1e2].into_iter().enumerate() {
+        weights[i * 20] = 0.0;
+        weights[i * 20 + 1] = 0.5;
+
+        smoother.update_lambda(lambda).unwrap();
+        smoother.update_weights(&weights).unwrap();
+
+        let mut fresh = WhittakerSmoother::new(lambda, 2, n, None, Some(&weights)).unwrap();
+        fresh.update_boundary(boundary).unwrap();
+
+        for (a, b) in smoother
+            .smooth(&y)
+            .unwrap()
+            .iter()
+            .zip(fresh.smooth(&y).unwrap())
+        {
+            assert_relative_eq!(*a, b, epsilon = 1e-10);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use whittaker_eilers::{RobustLoss, WhittakerError, WhittakerSmoother};
+
+fn spiked_sine() -> (Vec<f64>, Vec<f64>) {
+    let clean = (0..200)
+        .map(|i| (i as f64 / 20.0).sin())
+        .collect::<Vec<f64>>();
+    let mut noisy = clean
+        .iter()
+        .enumerate()
+        .map(|(i, y)| y + 0.05 * ((i * 7919) % 13) as f64 / 13.0 - 0.025)
+        .collect::<Vec<f64>>();
+    noisy[100] += 10.0;
+    (clean, noisy)
+}
+
+#[test]
+fn robust_smoothing_ignores_spike() {
+    let (clean, noisy) = spiked_sine();
+
+    let mut smoother = WhittakerSmoother::new(1e2, 2, noisy.len(), None, None).unwrap();
+
+    let standard = smoother.smooth(&noisy).unwrap();
+
+    for loss in [
+        RobustLoss::Huber(1.345),
+        RobustLoss::Tukey(4.685),
+        RobustLoss::Cauchy(2.385),
+    ] {
+        let robust = smoother.smooth_robust(&noisy, loss, 50, 1e-6).unwrap();
+
+        assert!(robust.converged);
+        assert!(robust.robust_weights[100] < 0.1);
+        assert!((robust.smoothed[100] - clean[100]).abs() < (standard[100] - clean[100]).abs());
+    }
+}
+
+#[test]
+fn robust_smoothing_tukey_interpolates_outlier() {
+    let (clean, noisy) = spiked_sine();
+
+    let mut smoother = WhittakerSmoother::new(1e2, 2, noisy.len(), None, None).unwrap();
+
+    let robust = smoother
+        .smooth_robust(&noisy, RobustLoss::default(), 50, 1e-8)
+        .unwrap();
+
+    assert_eq!(robust.robust_weights[100], 0.0);
+    assert!((robust.smoothed[100] - clean[100]).abs() < 0.05);
+}
+
+#[test]
+fn robust_smoothing_restores_weights() {
+    let (_, noisy) = spiked_sine();
+    let mut weights = vec![1.0; noisy.len()];
+    weights[10] = 0.0;
+
+    let mut smoother = WhittakerSmoother::new(1e2, 2, noisy.len(), None, Some(&weights)).unwrap();
+
+    let before = smoother.smooth(&noisy).unwrap();
+    smoother
+        .smooth_robust(&noisy, RobustLoss::Huber(1.345), 10, 1e-6)
+        .unwrap();
+    let after = smoother.smooth(&noisy).unwrap();
+
+    assert_eq!(before, after);
+}
+
+#[test]
+fn robust_smoothing_length_mismatch() {
+    let mut smoother = WhittakerSmoother::new(1e2, 2, 10, None, None).unwrap();
+
+    assert!(smoother
+        .smooth_robust(&[1.0, 2.0, 3.0], RobustLoss::default(), 10, 1e-6)
+        .is_err());
+}
+
+#[test]
+fn robust_smoothing_invalid_tuning_constant() {
+    let mut smoother = WhittakerSmoother::new(1e2, 2, 10, None, None).unwrap();
+    let y = vec![1.0; 10];
+
+    for loss in [
+        RobustLoss::Huber(0.0),
+        RobustLoss::Tukey(-4.685),
+        RobustLoss::Cauchy(f64::NAN),
+        RobustLoss::Huber(f64::INFINITY),
+    ] {
+        assert_eq!(
+            smoother.smooth_robust(&y, loss, 10, 1e-6).unwrap_err(),
+            WhittakerError::InvalidParameter("tuning_constant")
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::common::noisy_sine;
+use approx::assert_relative_eq;
+use nalgebra::{DMatrix, DVector};
+use whittaker_eilers::{
+    LambdaSearch, SelectionCriterion, SerialCorrelation, SmoothOptimalOptions, WhittakerError,
+    WhittakerSmoother,
+};
+
+#[test]
+fn criteria_match_dense_computation() {
+    let y = noisy_sine(80);
+    let n = y.len();
+    let lambda: f64 = 200.0;
+    let smoother = WhittakerSmoother::new(lambda, 2, n, None, None).unwrap();
+
+    let d = DMatrix::from_fn(n - 2, n, |row, column| {
+        match column as isize - row as isize {
+            0 | 2 => 1.0,
+            1 => -2.0,
+            _ => 0.0,
+        }
+    });
+    let system = DMatrix::<f64>::identity(n, n) + lambda * d.transpose() * &d;
+    let inverse = system.clone().try_inverse().unwrap();
+    let z = &inverse * DVector::from_vec(y.clone());
+
+    let m = n as f64;
+    let edf = inverse.trace();
+    let rss = (0..n).map(|i| (y[i] - z[i]).powi(2)).sum::<f64>();
+    let roughness = (&d * &z).norm_squared();
+    let reml = (m - 2.0) * ((rss + lambda * roughness) / (m - 2.0)).ln()
+        + system.determinant().ln()
+        - (m - 2.0) * lambda.ln();
+
+    let expected = [
+        (
+            SelectionCriterion::GeneralizedCrossValidation,
+            m * rss / (m - edf).powi(2),
+        ),
+        (
+            SelectionCriterion::Aicc,
+            (rss / m).ln() + 1.0 + 2.0 * (edf + 1.0) / (m - edf - 2.0),
+        ),
+        (SelectionCriterion::Bic, m * (rss / m).ln() + m.ln() * edf),
+        (SelectionCriterion::Reml, reml),
+        (
+            SelectionCriterion::MallowsCp(0.01),
+            rss / 0.01 - m + 2.0 * edf,
+        ),
+    ];
+
+    for (criterion, score) in expected {
+        let result = smoother.smooth_and_score(&y, criterion).unwrap();
+        assert_eq!(result.criterion, criterion);
+        assert_relative_eq!(result.effective_degrees_of_freedom, edf, epsilon = 1e-8);
+        assert_relative_eq!(result.score, score, epsilon = 1e-6, max_relative = 1e-8);
+    }
+}
+
+#[test]
+fn cross_validation_score_is_the_error() {
+    let y = noisy_sine(50);
+    let smoother = WhittakerSmoother::new(100.0, 2, y.len(), None, None).unwrap();
+
+    let result = smoother.smooth_and_cross_validate(&y).unwrap();
+    assert_eq!(result.criterion, SelectionCriterion::CrossValidation);
+    assert_eq!(result.score, result.cross_validation_error);
+}
+
+#[test]
+fn every_criterion_selects_a_sensible_lambda() {
+    let y = noisy_sine(300);
+    let clean = (0..y.len())
+        .map(|i| (i as f64 / 10.0).sin())
+        .collect::<Vec<f64>>();
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    for criterion in [
+        SelectionCriterion::CrossValidation,
+        SelectionCriterion::GeneralizedCrossValidation,
+        SelectionCriterion::Aicc,
+        SelectionCriterion::Reml,
+        SelectionCriterion::MallowsCp(0.0133),
+    ] {
+        let optimal = smoother
+            .smooth_optimal_with_criterion(&y, false, criterion)
+            .unwrap()
+            .get_optimal();
+
+        let error = optimal
+            .smoothed
+            .iter()
+            .zip(&clean)
+            .map(|(z, c)| (z - c).powi(2))
+            .sum::<f64>()
+            / y.len() as f64;
+
+        assert!(
+            optimal.lambda > 1.0 && optimal.lambda < 1e6,
+            "{criterion:?} chose {}",
+            optimal.lambda
+        );
+        assert!(error < 0.005, "{criterion:?} error {error}");
+        assert!(optimal.effective_degrees_of_freedom < 50.0);
+    }
+}
+
+#[test]
+fn bic_selects_a_sensible_lambda_away_from_interpolation() {
+    let y = noisy_sine(300);
+    let mut smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    // BIC keeps falling as lambda approaches zero and the fit interpolates the data, so the search starts at 1.
+    let optimal = smoother
+        .smooth_optimal_with_options(
+            &y,
+            &SmoothOptimalOptions {
+                lambdas: LambdaSearch::Range {
+                    min: 1.0,
+                    max: 1e8,
+                    steps: 17,
+                },
+                criterion: SelectionCriterion::Bic,
+                serial_correlation: SerialCorrelation::Ignore,
+            },
+        )
+        .unwrap()
+        .get_optimal();
+
+    assert!(optimal.lambda > 10.0 && optimal.lambda < 1e4);
+    assert!(optimal.effective_degrees_of_freedom < 50.0);
+}
+
+#[test]
+fn mallows_cp_needs_positive_variance() {
+    let y = noisy_sine(50);
+    let smoother = WhittakerSmoother::new(100.0, 2, y.len(), None, None).unwrap();
+
+    assert_eq!(
+        smoother
+            .smooth_and_score(&y, SelectionCriterion::MallowsCp(0.0))
+            .unwrap_err(),
+        WhittakerError::InvalidParameter("noise_variance")
+    );
+}
@@ -0,0 +1,111 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{ShapeConstraint, WhittakerError, WhittakerSmoother};
+
+fn noise(i: usize) -> f64 {
+    0.3 * ((i * 7919) % 23) as f64 / 23.0 - 0.15
+}
+
+fn calibration_curve(length: usize) -> Vec<f64> {
+    (0..length)
+        .map(|i| (i as f64 / length as f64 * 3.0).tanh() + noise(i))
+        .collect()
+}
+
+fn first_differences(z: &[f64]) -> Vec<f64> {
+    z.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+#[test]
+fn monotone_constraints_remove_wiggles() {
+    let y = calibration_curve(100);
+    let smoother = WhittakerSmoother::new(1.0, 2, y.len(), None, None).unwrap();
+
+    let unconstrained = smoother.smooth(&y).unwrap();
+    assert!(first_differences(&unconstrained).iter().any(|d| *d < -1e-3));
+
+    let result = smoother
+        .smooth_shape_constrained(&y, ShapeConstraint::MonotoneIncreasing, 50)
+        .unwrap();
+
+    assert!(result.converged);
+    assert!(result.iterations > 0);
+    assert!(first_differences(&result.smoothed)
+        .iter()
+        .all(|d| *d > -1e-5));
+
+    // Decreasing data is the mirror image.
+    let negated = y.iter().map(|y| -y).collect::<Vec<f64>>();
+    let decreasing = smoother
+        .smooth_shape_constrained(&negated, ShapeConstraint::MonotoneDecreasing, 50)
+        .unwrap();
+
+    assert!(decreasing.converged);
+    for (a, b) in decreasing.smoothed.iter().zip(&result.smoothed) {
+        assert_relative_eq!(*a, -b, epsilon = 1e-10);
+    }
+}
+
+#[test]
+fn concave_constraint_with_unequal_spacing() {
+    let x = (0..80)
+        .map(|i| i as f64 * 0.1 + 0.03 * (i as f64 * 1.9).sin())
+        .collect::<Vec<f64>>();
+    let y = x
+        .iter()
+        .enumerate()
+        .map(|(i, x)| x.sqrt() + noise(i))
+        .collect::<Vec<f64>>();
+    let smoother = WhittakerSmoother::new(1e-3, 3, y.len(), Some(&x), None).unwrap();
+
+    let result = smoother
+        .smooth_shape_constrained(&y, ShapeConstraint::Concave, 50)
+        .unwrap();
+    assert!(result.converged);
+
+    let z = &result.smoothed;
+    for i in 0..z.len() - 2 {
+        let left = (z[i + 1] - z[i]) / (x[i + 1] - x[i]);
+        let right = (z[i + 2] - z[i + 1]) / (x[i + 2] - x[i + 1]);
+        assert!(right - left < 1e-5);
+    }
+
+    let convex = smoother
+        .smooth_shape_constrained(&y, ShapeConstraint::Convex, 50)
+        .unwrap();
+    assert!(convex.converged);
+
+    // Convex data can only curve upwards, so the best convex fit of a concave curve is close to a straight line.
+    let z = &convex.smoothed;
+    let slope = (z[z.len() - 1] - z[0]) / (x[x.len() - 1] - x[0]);
+    for i in 1..z.len() {
+        assert_relative_eq!((z[i] - z[i - 1]) / (x[i] - x[i - 1]), slope, epsilon = 1e-3);
+    }
+}
+
+#[test]
+fn satisfied_constraint_does_not_change_smoothing() {
+    let y = (0..50).map(|i| (i as f64).powi(2)).collect::<Vec<f64>>();
+    let mut weights = vec![1.0; y.len()];
+    weights[10] = 0.0;
+    let smoother = WhittakerSmoother::new(1e2, 2, y.len(), None, Some(&weights)).unwrap();
+
+    let result = smoother
+        .smooth_shape_constrained(&y, ShapeConstraint::Convex, 10)
+        .unwrap();
+
+    assert!(result.converged);
+    assert_eq!(result.iterations, 0);
+    assert_eq!(result.smoothed, smoother.smooth(&y).unwrap());
+}
+
+#[test]
+fn shape_constraint_rejects_short_data() {
+    let smoother = WhittakerSmoother::new(1e2, 1, 2, None, None).unwrap();
+
+    assert_eq!(
+        smoother
+            .smooth_shape_constrained(&[1.0, 2.0], ShapeConstraint::Concave, 10)
+            .unwrap_err(),
+        WhittakerError::DataTooShort(2, 2)
+    );
+}
@@ -0,0 +1,184 @@
+use approx::assert_relative_eq;
+use whittaker_eilers::{WhittakerError, WhittakerSmoother, WhittakerSmoother2D};
+
+fn noisy_surface(rows: usize, columns: usize) -> Vec<f64> {
+    (0..rows * columns)
+        .map(|i| {
+            let (row, column) = ((i / columns) as f64, (i % columns) as f64);
+            (row / 5.0).sin() * (column / 7.0).cos() + 0.1 * ((i * 7919) % 17) as f64 / 17.0
+        })
+        .collect()
+}
+
+#[test]
+fn array_algorithm_matches_sparse_solve() {
+    let (rows, columns) = (20, 30);
+    let surface = noisy_surface(rows, columns);
+
+    let array_smoother =
+        WhittakerSmoother2D::new((10.0, 50.0), (2, 3), (rows, columns), (None, None), None)
+            .unwrap();
+    let sparse_smoother = WhittakerSmoother2D::new(
+        (10.0, 50.0),
+        (2, 3),
+        (rows, columns),
+        (None, None),
+        Some(&vec![1.0; rows * columns]),
+    )
+    .unwrap();
+
+    let array_result = array_smoother.smooth(&surface).unwrap();
+    let sparse_result = sparse_smoother.smooth(&surface).unwrap();
+
+    for (a, b) in array_result.iter().zip(&sparse_result) {
+        assert_relative_eq!(a, b, epsilon = 1e-8);
+    }
+}
+
+#[test]
+fn rows_smoothed_independently_without_row_penalty() {
+    let (rows, columns) = (4, 50);
+    let surface = noisy_surface(rows, columns);
+    let x_columns = (0..columns)
+        .map(|x| (x as f64).powf(1.2))
+        .collect::<Vec<f64>>();
+
+    let smoother_2d = WhittakerSmoother2D::new(
+        (0.0, 1e3),
+        (2, 2),
+        (rows, columns),
+        (None, Some(&x_columns)),
+        None,
+    )
+    .unwrap();
+    let smoother_1d = WhittakerSmoother::new(1e3, 2, columns, Some(&x_columns), None).unwrap();
+
+    let smoothed = smoother_2d.smooth(&surface).unwrap();
+
+    for row in 0..rows {
+        let expected = smoother_1d
+            .smooth(&surface[row * columns..(row + 1) * columns])
+            .unwrap();
+        for (a, b) in smoothed[row * columns..(row + 1) * columns]
+            .iter()
+            .zip(&expected)
+        {
+            assert_relative_eq!(a, b, epsilon = 1e-8);
+        }
+    }
+}
+
+#[test]
+fn plane_interpolated_through_gap() {
+    let (rows, columns) = (10, 12);
+    let plane = (0..rows * columns)
+        .map(|i| 1.0 + 0.5 * (i / columns) as f64 - 0.25 * (i % columns) as f64)
+        .collect::<Vec<f64>>();
+    let mut with_gap = plane.clone();
+    let mut weights = vec![1.0; rows * columns];
+    for index in [40, 41, 52, 53] {
+        with_gap[index] = 100.0;
+        weights[index] = 0.0;
+    }
+
+    let mut smoother =
+        WhittakerSmoother2D::new((1e2, 1e2), (2, 2), (rows, columns), (None, None), None).unwrap();
+    smoother.update_weights(&weights).unwrap();
+
+    let smoothed = smoother.smooth(&with_gap).unwrap();
+
+    for (a, b) in smoothed.iter().zip(&plane) {
+        assert_relative_eq!(a, b, epsilon = 1e-6);
+    }
+
+    smoother.update_lambdas((1e4, 1e1)).unwrap();
+    smoother.update_orders((3, 2)).unwrap();
+    let smoothed = smoother.smooth(&with_gap).unwrap();
+
+    for (a, b) in smoothed.iter().zip(&plane) {
+        assert_relative_eq!(a, b, epsilon = 1e-6);
+    }
+}
+
+#[test]
+fn grid_errors() {
+    assert_eq!(
+        WhittakerSmoother2D::new((1.0, 1.0), (3, 2), (2, 10), (None, None), None).err(),
+        Some(WhittakerError::DataTooShort(2, 3))
+    );
+    assert_eq!(
+        WhittakerSmoother2D::new(
+            (1.0, 1.0),
+            (2, 2),
+            (5, 10),
+            (None, None),
+            Some(&vec![1.0; 49])
+        )
+        .err(),
+        Some(WhittakerError::LengthMismatch(50, 49))
+    );
+    assert_eq!(
+        WhittakerSmoother2D::new(
+            (1.0, 1.0),
+            (2, 2),
+            (5, 10),
+            (Some(&vec![0.0, 1.0, 1.0, 2.0, 3.0]), None),
+            None
+        )
+        .err(),
+        Some(WhittakerError::NotMonotonicallyIncreasing(1))
+    );
+
+    assert_eq!(
+        WhittakerSmoother2D::new((1.0, -1.0), (2, 2), (5, 10), (None, None), None).err(),
+        Some(WhittakerError::InvalidParameter("lambda"))
+    );
+
+    let mut smoother =
+        WhittakerSmoother2D::new((1.0, 1.0), (2, 2), (5, 10), (None, None), None).unwrap();
+    assert!(smoother.smooth(&[1.0; 10]).is_err());
+    assert_eq!(
+        smoother.update_lambdas((f64::NAN, 1.0)),
+        Err(WhittakerError::InvalidParameter("lambda"))
+    );
+
+    let mut weights = vec![1.0; 50];
+    weights[7] = f64::INFINITY;
+    assert_eq!(
+        smoother.update_weights(&weights),
+        Err(WhittakerError::InvalidValue("weights", 7))
+    );
+    assert_eq!(
+        smoother.update_weights(&[0.0; 50]),
+        Err(WhittakerError::AllWeightsZero)
+    );
+    assert_eq!(smoother.get_lambdas(), (1.0, 1.0));
+}
+
+#[test]
+fn long_axis_without_weights_uses_sparse_solve() {
+    // Axes beyond the array algorithm's cutoff are solved with the sparse system even without weights.
+    let (rows, columns) = (3, 600);
+    let surface = noisy_surface(rows, columns);
+
+    let unweighted =
+        WhittakerSmoother2D::new((10.0, 50.0), (2, 2), (rows, columns), (None, None), None)
+            .unwrap();
+    let weighted = WhittakerSmoother2D::new(
+        (10.0, 50.0),
+        (2, 2),
+        (rows, columns),
+        (None, None),
+        Some(&vec![1.0; rows * columns]),
+    )
+    .unwrap();
+
+    for (a, b) in unweighted
+        .smooth(&surface)
+        .unwrap()
+        .iter()
+        .zip(&weighted.smooth(&surface).unwrap())
+    {
+        assert_relative_eq!(a, b, epsilon = 1e-10);
+    }
+}
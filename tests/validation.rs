@@ -1,4 +1,5 @@
 use approx::assert_relative_eq;
+use nalgebra::{DMatrix, DVector};
 use std::{
     fs::File,
     io::{BufRead, BufReader},
@@ -235,7 +236,7 @@ fn cross_validation_no_weights() {
         .smooth_and_cross_validate(&input_data.y)
         .unwrap();
 
-    assert_relative_eq!(cve.cross_validation_error, 3.3568, epsilon = 1e-4); // Produced from matlab scripts.
+    assert_relative_eq!(cve.cross_validation_error, 3.3575, epsilon = 1e-4); // Exact hat matrix. The matlab scripts' 100 point approximation gives 3.3568.
 
     whittaker_smoother.update_order(3).unwrap();
 
@@ -243,7 +244,7 @@ fn cross_validation_no_weights() {
         .smooth_and_cross_validate(&input_data.y)
         .unwrap();
 
-    assert_relative_eq!(cve.cross_validation_error, 2.6859, epsilon = 1e-4);
+    assert_relative_eq!(cve.cross_validation_error, 2.6896, epsilon = 1e-4); // Approximated: 2.6859.
 }
 #[test]
 fn cross_validation_weights_100() {
@@ -281,7 +282,7 @@ fn cross_validation_weights() {
         .smooth_and_cross_validate(&input_data.y)
         .unwrap();
 
-    assert_relative_eq!(cve.cross_validation_error, 3.4549, epsilon = 1e-4);
+    assert_relative_eq!(cve.cross_validation_error, 3.4826, epsilon = 1e-4); // Approximated: 3.4549.
 }
 #[test]
 fn cross_validation_weights_x_input_100() {
@@ -319,7 +320,7 @@ fn cross_validation_weights_x_input() {
         .smooth_and_cross_validate(&input_data.y)
         .unwrap();
 
-    assert_relative_eq!(cve.cross_validation_error, 3.0762, epsilon = 1e-4);
+    assert_relative_eq!(cve.cross_validation_error, 3.2230, epsilon = 1e-4); // Approximated: 3.0762.
 }
 #[test]
 fn cross_validation_x_input_100() {
@@ -357,7 +358,46 @@ fn cross_validation_x_input() {
         .smooth_and_cross_validate(&input_data.y)
         .unwrap();
 
-    assert_relative_eq!(cve.cross_validation_error, 3.0413, epsilon = 1e-4);
+    assert_relative_eq!(cve.cross_validation_error, 3.0425, epsilon = 1e-4); // Approximated: 3.0413.
+}
+
+#[test]
+fn cross_validation_exact_hat_matrix() {
+    let input_data = read_input_to_vecs();
+    let n = 300;
+    let (x, y, weights) = (
+        input_data.x[..n].to_vec(),
+        input_data.y[..n].to_vec(),
+        input_data.random_weights[..n].to_vec(),
+    );
+
+    let whittaker_smoother = WhittakerSmoother::new(2e4, 3, n, Some(&x), Some(&weights)).unwrap();
+
+    let cve = whittaker_smoother.smooth_and_cross_validate(&y).unwrap();
+
+    // Dense third order divided differences, as built by Eilers' ddmat.
+    let mut d = DMatrix::<f64>::identity(n, n);
+    for order in 1..=3 {
+        let rows = n - order;
+        d = DMatrix::from_fn(rows, n, |row, column| {
+            (d[(row + 1, column)] - d[(row, column)]) / (x[row + order] - x[row])
+        });
+    }
+    let inverse = (DMatrix::from_diagonal(&DVector::from_vec(weights.clone()))
+        + 2e4 * d.transpose() * d)
+        .try_inverse()
+        .unwrap();
+
+    let expected = ((0..n)
+        .map(|i| {
+            let r = (y[i] - cve.smoothed[i]) / (1.0 - inverse[(i, i)] * weights[i]);
+            weights[i] * r * r
+        })
+        .sum::<f64>()
+        / weights.iter().sum::<f64>())
+    .sqrt();
+
+    assert_relative_eq!(cve.cross_validation_error, expected, epsilon = 1e-8);
 }
 
 // #[test]
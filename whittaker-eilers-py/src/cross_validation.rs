@@ -47,4 +47,12 @@ impl CrossValidationResult {
     pub fn get_cross_validation_error(&self) -> f64 {
         self.0.cross_validation_error
     }
+    /// The score given to the smoothed data by the selection criterion. Lower is better.
+    pub fn get_score(&self) -> f64 {
+        self.0.score
+    }
+    /// The effective degrees of freedom of the fit, the trace of the hat matrix.
+    pub fn get_effective_degrees_of_freedom(&self) -> f64 {
+        self.0.effective_degrees_of_freedom
+    }
 }
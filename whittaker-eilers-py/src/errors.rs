@@ -19,6 +19,9 @@ impl std::convert::From<WhittakerError> for PyErr {
             WhittakerErrorRs::MatrixNotInvertible => {
                 MatrixNotInvertible::new_err(err.0.to_string())
             }
+            WhittakerErrorRs::InvalidParameter(_) => InvalidParameter::new_err(err.0.to_string()),
+            WhittakerErrorRs::InvalidValue(_, _) => InvalidValue::new_err(err.0.to_string()),
+            WhittakerErrorRs::AllWeightsZero => AllWeightsZero::new_err(err.0.to_string()),
         }
     }
 }
@@ -29,3 +32,6 @@ create_exception!(whittaker_eilers, SolverError, PyException);
 create_exception!(whittaker_eilers, SampleRateError, PyException);
 create_exception!(whittaker_eilers, NotMonotonicallyIncreasing, PyException);
 create_exception!(whittaker_eilers, MatrixNotInvertible, PyException);
+create_exception!(whittaker_eilers, InvalidParameter, PyException);
+create_exception!(whittaker_eilers, InvalidValue, PyException);
+create_exception!(whittaker_eilers, AllWeightsZero, PyException);
@@ -72,6 +72,20 @@ impl WhittakerSmoother {
         self.0.update_weights(&weights).map_err(map_err_to_py)
     }
 
+    /// Updates the penalty weights, which vary the smoothness along the data.
+    ///
+    /// Penalty weights scale the roughness penalty point by point, so the system solved becomes `W + λD'VD`. Weights above 1 smooth
+    /// a stretch more and weights below 1 smooth it less.
+    ///
+    /// Parameters
+    /// ----------
+    /// penalty_weights : The penalty weight of each point. Must be as long as the data, positive, and finite.
+    pub fn update_penalty_weights(&mut self, penalty_weights: Vec<f64>) -> PyResult<()> {
+        self.0
+            .update_penalty_weights(&penalty_weights)
+            .map_err(map_err_to_py)
+    }
+
     /// Updates the order of the Whittaker-Eilers smoother.
     ///
     /// Efficiently updates the order at which the Whittaker will use to smooth the data.
@@ -134,11 +148,35 @@ impl WhittakerSmoother {
             .collect()
     }
 
+    /// Run Whittaker-Eilers smoothing and estimate a derivative of the smoothed data.
+    ///
+    /// For unequally spaced data, the divided differences that the smoother penalises are used, giving the derivative with respect to `x_input`.
+    /// For equally spaced data, the plain differences are used, giving the derivative with respect to the sample index. Each difference is
+    /// interpolated back to the sample positions and, at the boundaries, linearly extrapolated from the two nearest differences.
+    ///
+    /// Parameters
+    /// ----------
+    /// y_vals : The values which are to be smoothed.
+    /// derivative_order : The order of the derivative. 1 gives the velocity of position data, 2 the acceleration. Must be less than the data length.
+    ///
+    /// Returns
+    /// -------
+    /// The derivative of the smoothed data at each sample position.
+    pub fn smooth_derivative(
+        &self,
+        y_vals: Vec<f64>,
+        derivative_order: usize,
+    ) -> PyResult<Vec<f64>> {
+        self.0
+            .smooth_derivative(&y_vals, derivative_order)
+            .map_err(map_err_to_py)
+    }
+
     /// Run Whittaker-Eilers smoothing, interpolation and cross validation.
     ///
     /// This function will run the smoother and assess the cross validation error on the result. This is defined in Eiler's
-    /// 2003 paper: "A Perfect Smoother".  It involves computing the "hat matrix" or "smoother matrix" which inverses a sparse matrix. The
-    /// inverse of a sparse matrix is usually dense, so this function will take much longer to run in comparison to just running `smooth`.
+    /// 2003 paper: "A Perfect Smoother".  It involves computing the diagonal of the "hat matrix" or "smoother matrix". Only the
+    /// elements of the inverse within the band of the factorised system are computed, so this remains linear in the length of the data, though slower than just running `smooth`.
     ///
     /// Parameters
     /// ----------
@@ -157,14 +195,14 @@ impl WhittakerSmoother {
     }
     /// Runs Whittaker-Eilers smoothing for a variety of lambdas and selects the optimally smoothed time series.
     ///
-    /// This function runs the smoother for lambdas varying from 1e-2 to 1e8 in logarithmic steps of 0.5. It computes the
+    /// This function runs the smoother for lambdas varying from 1e-5 to 1e8 in logarithmic steps of 0.5. It computes the
     /// hat/smoother matrix and finds the optimal lambda for the data. If the time-series exhibits serial correlation the optimal
     /// lambda can be very small and mean the smoothed data doesn't differ from the input data. To avoid this, use `break_serial_correlation = true`
     ///
     /// It will return the smoothed data, lambda, and cross validation error for each lambda tested!
     ///
-    /// As the smoother matrix requires the inversion of a sparse matrix (which usually becomes a dense matrix), this code is extremely slow compared to smoothing
-    /// with a known lambda. Use sparingly!
+    /// As each lambda requires a new factorisation and the diagonal of the smoother matrix, this code is much slower than smoothing
+    /// with a known lambda.
     ///
     /// Parameters
     /// ----------